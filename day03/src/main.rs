@@ -1,48 +1,207 @@
 use std::{fs::File, io::Read};
 
-use regex::Regex;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    Mul(usize, usize),
+    Do,
+    Dont,
+}
+
+/// Scans corrupted memory byte by byte for `mul(X,Y)`, `do()` and `don't()`,
+/// where X and Y are one to three ASCII digits. Never allocates.
+struct InstructionScanner<'a> {
+    memory: &'a [u8],
+    position: usize,
+}
+
+impl<'a> InstructionScanner<'a> {
+    fn new(memory: &'a [u8]) -> Self {
+        InstructionScanner {
+            memory,
+            position: 0,
+        }
+    }
+
+    fn read_number(&self, start: usize, terminator: u8) -> Option<(usize, usize)> {
+        let mut value = 0;
+        let mut idx = start;
+        while idx < self.memory.len() && idx - start < 3 && self.memory[idx].is_ascii_digit() {
+            value = value * 10 + (self.memory[idx] - b'0') as usize;
+            idx += 1;
+        }
+        if idx == start || self.memory.get(idx) != Some(&terminator) {
+            return None;
+        }
+        Some((value, idx + 1))
+    }
+
+    fn read_mul(&self, start: usize) -> Option<(Instruction, usize)> {
+        let (left, after_left) = self.read_number(start + b"mul(".len(), b',')?;
+        let (right, end) = self.read_number(after_left, b')')?;
+        Some((Instruction::Mul(left, right), end))
+    }
+
+    fn read_at(&self, start: usize) -> Option<(Instruction, usize)> {
+        let rest = &self.memory[start..];
+        if rest.starts_with(b"mul(") {
+            self.read_mul(start)
+        } else if rest.starts_with(b"do()") {
+            Some((Instruction::Do, start + b"do()".len()))
+        } else if rest.starts_with(b"don't()") {
+            Some((Instruction::Dont, start + b"don't()".len()))
+        } else {
+            None
+        }
+    }
+}
+
+impl Iterator for InstructionScanner<'_> {
+    type Item = Instruction;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position < self.memory.len() {
+            match self.read_at(self.position) {
+                Some((instruction, end)) => {
+                    self.position = end;
+                    return Some(instruction);
+                }
+                None => self.position += 1,
+            }
+        }
+        None
+    }
+}
+
+fn sum_all_muls(memory: &[u8]) -> usize {
+    InstructionScanner::new(memory)
+        .map(|instruction| match instruction {
+            Instruction::Mul(left, right) => left * right,
+            Instruction::Do | Instruction::Dont => 0,
+        })
+        .sum()
+}
+
+fn sum_enabled_muls(memory: &[u8]) -> usize {
+    let mut enabled = true;
+    let mut sum = 0;
+    for instruction in InstructionScanner::new(memory) {
+        match instruction {
+            Instruction::Mul(left, right) if enabled => sum += left * right,
+            Instruction::Mul(_, _) => {}
+            Instruction::Do => enabled = true,
+            Instruction::Dont => enabled = false,
+        }
+    }
+    sum
+}
 
 fn main() {
     let file_name = "input";
     let mut input_file = File::open(file_name).expect("Can open file");
-    let mut content = String::new();
-    content.push_str("do()");
-    input_file
-        .read_to_string(&mut content)
-        .expect("Can read file");
-    content.push_str("don't()");
-    let expression = Regex::new(r"mul\((\d{1,3})\,(\d{1,3})\)").expect("Is valid Regex");
-    let mut values: Vec<(usize, usize)> = Vec::new();
-    for (_, [left, right]) in expression.captures_iter(&content).map(|c| c.extract()) {
-        values.push((
-            left.parse().expect("parsed digits for left"),
-            right.parse().expect("parsed digits for right"),
-        ));
-    }
-    let part_one_answer: usize = values.iter().map(|(l, r)| l * r).sum();
-    println!("Part 01: {}", part_one_answer);
-    let mut indexed_strs: Vec<_> = Vec::new();
-    content.split("do()").enumerate().for_each(|(i, do_part)| {
-        do_part
-            .split("don't()")
-            .enumerate()
-            .for_each(|(j, dont_part)| {
-                indexed_strs.push((i, j, dont_part));
-            })
-    });
-    let mut values_second: Vec<(usize, usize)> = Vec::new();
-    indexed_strs
-        .iter()
-        .filter(|(_i, j, _part)| *j == 0)
-        .map(|(_, _, part)| *part)
-        .for_each(|haystack| {
-        for (_, [left, right]) in expression.captures_iter(haystack).map(|c| c.extract()) {
-            values_second.push((
-                left.parse().expect("parsed digits for left"),
-                right.parse().expect("parsed digits for right"),
-            ));
-        }
-    });
-    let part_two_answer: usize = values_second.iter().map(|(l, r)| l * r).sum();
-    println!("Part 02: {}", part_two_answer);
+    let mut content = Vec::new();
+    input_file.read_to_end(&mut content).expect("Can read file");
+    println!("Part 01: {}", sum_all_muls(&content));
+    println!("Part 02: {}", sum_enabled_muls(&content));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::bytes::Regex;
+
+    /// Digits as bytes, parsed the way the regex captured them.
+    fn parse_digits(digits: &[u8]) -> usize {
+        std::str::from_utf8(digits)
+            .expect("digits are ASCII")
+            .parse()
+            .expect("parsed digits")
+    }
+
+    /// The original regex based solution, kept as the reference implementation.
+    /// It works on bytes so that memory does not have to be valid UTF-8.
+    fn regex_part_one(expression: &Regex, memory: &[u8]) -> usize {
+        expression
+            .captures_iter(memory)
+            .map(|c| c.extract())
+            .map(|(_, [left, right])| parse_digits(left) * parse_digits(right))
+            .sum()
+    }
+
+    fn regex_part_two(expression: &Regex, memory: &[u8]) -> usize {
+        let enable = Regex::new(r"do\(\)").expect("Is valid Regex");
+        let disable = Regex::new(r"don't\(\)").expect("Is valid Regex");
+        let content = [b"do()", memory, b"don't()"].concat();
+        enable
+            .split(&content)
+            .filter_map(|do_part| disable.split(do_part).next())
+            .map(|haystack| regex_part_one(expression, haystack))
+            .sum()
+    }
+
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+    }
+
+    /// Random bytes, biased towards fragments of valid instructions so that
+    /// matches and near misses both show up regularly. Any byte can show up,
+    /// so the memory is often not valid UTF-8.
+    fn random_memory(rng: &mut XorShift) -> Vec<u8> {
+        const FRAGMENTS: [&str; 12] = [
+            "mul(", "do()", "don't()", ",", ")", "(", "1", "23", "456", "7890", "mu", "don",
+        ];
+        let length = rng.next() % 40;
+        let mut memory = Vec::new();
+        for _ in 0..length {
+            if rng.next().is_multiple_of(2) {
+                memory.extend_from_slice(
+                    FRAGMENTS[(rng.next() % FRAGMENTS.len() as u64) as usize].as_bytes(),
+                );
+            } else {
+                memory.push(rng.next() as u8);
+            }
+        }
+        memory
+    }
+
+    #[test]
+    fn example() {
+        let memory = b"xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        assert_eq!(161, sum_all_muls(memory));
+        assert_eq!(48, sum_enabled_muls(memory));
+    }
+
+    #[test]
+    fn rejects_long_numbers() {
+        assert_eq!(0, sum_all_muls(b"mul(1234,5)mul(5,1234)mul(,5)mul(5,)"));
+        assert_eq!(15, sum_all_muls(b"mul(mul(3,5)"));
+    }
+
+    #[test]
+    fn matches_regex_on_random_memory() {
+        // ASCII digits only, as `\d` also matches other digits in valid UTF-8.
+        let expression = Regex::new(r"(?-u)mul\((\d{1,3})\,(\d{1,3})\)").expect("Is valid Regex");
+        let mut rng = XorShift(0x2024_1203);
+        for _ in 0..20_000 {
+            let memory = random_memory(&mut rng);
+            assert_eq!(
+                regex_part_one(&expression, &memory),
+                sum_all_muls(&memory),
+                "part one differs on {:?}",
+                memory
+            );
+            assert_eq!(
+                regex_part_two(&expression, &memory),
+                sum_enabled_muls(&memory),
+                "part two differs on {:?}",
+                memory
+            );
+        }
+    }
 }
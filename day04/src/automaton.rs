@@ -0,0 +1,128 @@
+use std::collections::{HashMap, VecDeque};
+
+/// Aho-Corasick automaton over characters. Finds all occurrences of all
+/// patterns in a single pass over the text, including overlapping ones.
+pub struct Automaton {
+    transitions: Vec<HashMap<char, usize>>,
+    fail: Vec<usize>,
+    /// Pattern ids ending at each node, including those of its fail chain.
+    outputs: Vec<Vec<usize>>,
+    lengths: Vec<usize>,
+}
+
+impl Automaton {
+    pub fn new<I, P>(patterns: I) -> Automaton
+    where
+        I: IntoIterator<Item = P>,
+        P: IntoIterator<Item = char>,
+    {
+        let mut automaton = Automaton {
+            transitions: vec![HashMap::new()],
+            fail: vec![0],
+            outputs: vec![Vec::new()],
+            lengths: Vec::new(),
+        };
+        for (id, pattern) in patterns.into_iter().enumerate() {
+            let mut node = 0;
+            let mut length = 0;
+            for c in pattern {
+                node = match automaton.transitions[node].get(&c) {
+                    Some(next) => *next,
+                    None => {
+                        automaton.transitions.push(HashMap::new());
+                        automaton.fail.push(0);
+                        automaton.outputs.push(Vec::new());
+                        let next = automaton.transitions.len() - 1;
+                        automaton.transitions[node].insert(c, next);
+                        next
+                    }
+                };
+                length += 1;
+            }
+            if length > 0 {
+                automaton.outputs[node].push(id);
+            }
+            automaton.lengths.push(length);
+        }
+        automaton.build_fail_links();
+        automaton
+    }
+
+    fn build_fail_links(&mut self) {
+        let mut queue: VecDeque<usize> = self.transitions[0].values().copied().collect();
+        while let Some(node) = queue.pop_front() {
+            let edges: Vec<(char, usize)> = self.transitions[node]
+                .iter()
+                .map(|(c, n)| (*c, *n))
+                .collect();
+            for (c, child) in edges {
+                let mut fallback = self.fail[node];
+                while fallback != 0 && !self.transitions[fallback].contains_key(&c) {
+                    fallback = self.fail[fallback];
+                }
+                let child_fail = match self.transitions[fallback].get(&c) {
+                    Some(target) if *target != child => *target,
+                    _ => 0,
+                };
+                self.fail[child] = child_fail;
+                let inherited = self.outputs[child_fail].clone();
+                self.outputs[child].extend(inherited);
+                queue.push_back(child);
+            }
+        }
+    }
+
+    pub fn pattern_len(&self, id: usize) -> usize {
+        self.lengths[id]
+    }
+
+    /// Calls `on_match(pattern_id, end)` for every occurrence, where `end` is
+    /// the index of the last character of the occurrence in `text`.
+    pub fn scan<I, F>(&self, text: I, mut on_match: F)
+    where
+        I: IntoIterator<Item = char>,
+        F: FnMut(usize, usize),
+    {
+        let mut node = 0;
+        for (idx, c) in text.into_iter().enumerate() {
+            loop {
+                if let Some(next) = self.transitions[node].get(&c) {
+                    node = *next;
+                    break;
+                }
+                if node == 0 {
+                    break;
+                }
+                node = self.fail[node];
+            }
+            for id in &self.outputs[node] {
+                on_match(*id, idx);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_all(patterns: &[&str], text: &str) -> Vec<(usize, usize)> {
+        let automaton = Automaton::new(patterns.iter().map(|p| p.chars()));
+        let mut found = Vec::new();
+        automaton.scan(text.chars(), |id, end| found.push((id, end)));
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn overlapping_and_nested_patterns() {
+        assert_eq!(
+            vec![(0, 3), (1, 3), (1, 5), (2, 5)],
+            find_all(&["XMAS", "AS", "SAS"], "XMASAS")
+        );
+        assert_eq!(
+            vec![(0, 1), (0, 2), (0, 3), (1, 2), (1, 3)],
+            find_all(&["AA", "AAA"], "AAAA")
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{pattern::PatternMatch, search::WordMatch};
+
+fn json_string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_cells(cells: &[(usize, usize)]) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|(x, y)| format!("[{},{}]", x, y))
+        .collect();
+    format!("[{}]", cells.join(","))
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_cells(cells: &[(usize, usize)]) -> String {
+    let cells: Vec<String> = cells.iter().map(|(x, y)| format!("{} {}", x, y)).collect();
+    cells.join(";")
+}
+
+/// Both parts as one JSON object with a `part_one` list of word matches and
+/// a `part_two` list of pattern matches.
+pub fn to_json(words: &[WordMatch], patterns: &[PatternMatch]) -> String {
+    let words: Vec<String> = words
+        .iter()
+        .map(|m| {
+            format!(
+                "{{\"word\":{},\"x\":{},\"y\":{},\"direction\":\"{:?}\",\"cells\":{}}}",
+                json_string(&m.word),
+                m.x,
+                m.y,
+                m.direction,
+                json_cells(&m.cells)
+            )
+        })
+        .collect();
+    let patterns: Vec<String> = patterns
+        .iter()
+        .map(|m| {
+            format!(
+                "{{\"pattern\":{},\"x\":{},\"y\":{},\"rotation\":{},\"reflected\":{},\"cells\":{}}}",
+                json_string(&m.pattern),
+                m.x,
+                m.y,
+                m.orientation.rotation * 90,
+                m.orientation.reflected,
+                json_cells(&m.cells)
+            )
+        })
+        .collect();
+    format!(
+        "{{\"part_one\":[{}],\"part_two\":[{}]}}\n",
+        words.join(","),
+        patterns.join(",")
+    )
+}
+
+/// One row per match. Cells are written as `x y` pairs separated by `;`.
+pub fn to_csv(words: &[WordMatch], patterns: &[PatternMatch]) -> String {
+    let mut out = String::from("part,name,x,y,orientation,cells\n");
+    for m in words {
+        out.push_str(&format!(
+            "1,{},{},{},{:?},{}\n",
+            csv_field(&m.word),
+            m.x,
+            m.y,
+            m.direction,
+            csv_cells(&m.cells)
+        ));
+    }
+    for m in patterns {
+        out.push_str(&format!(
+            "2,{},{},{},{},{}\n",
+            csv_field(&m.pattern),
+            m.x,
+            m.y,
+            csv_field(&m.orientation.to_string()),
+            csv_cells(&m.cells)
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_game_map, pattern::Pattern};
+
+    #[test]
+    fn exports_both_parts() {
+        let map = build_game_map("example");
+        let words = map.find_words(&["XMAS".to_string()]);
+        let patterns = map.find_pattern(&Pattern::x_mas());
+        let json = to_json(&words[..1], &patterns[..1]);
+        assert_eq!(
+            "{\"part_one\":[{\"word\":\"XMAS\",\"x\":5,\"y\":0,\"direction\":\"LeftToRight\",\
+             \"cells\":[[5,0],[6,0],[7,0],[8,0]]}],\"part_two\":[{\"pattern\":\"X-MAS\",\
+             \"x\":1,\"y\":0,\"rotation\":0,\"reflected\":false,\"cells\":[[1,0],[1,2],[2,1],[3,0],[3,2]]}]}\n",
+            json
+        );
+        let csv = to_csv(&words[..1], &patterns[..1]);
+        assert_eq!(
+            "part,name,x,y,orientation,cells\n\
+             1,XMAS,5,0,LeftToRight,5 0;6 0;7 0;8 0\n\
+             2,X-MAS,1,0,rotated 0,1 0;1 2;2 1;3 0;3 2\n",
+            csv
+        );
+    }
+
+    #[test]
+    fn escapes_names() {
+        assert_eq!("\"a\\\"b\"", json_string("a\"b"));
+        assert_eq!("\"M.S/.A.,\"\"\"", csv_field("M.S/.A.,\""));
+    }
+}
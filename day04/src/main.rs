@@ -1,15 +1,29 @@
+mod automaton;
+mod export;
+mod overlap;
+mod pattern;
+mod render;
+mod search;
+
 use crate::Direction::{
     BottomToTop, LeftToRight, NorthEastToSouthWest, NorthWestToSouthEast, RightToLeft,
     SouthEastToNorthWest, SouthWestToNorthEast, TopToBottom,
 };
 
 use std::{
+    env,
     fmt::{Display, Formatter, Result},
-    fs::File,
+    fs::{self, File},
     io::Read,
     slice::Iter,
 };
 
+use overlap::OverlapPolicy;
+use pattern::Pattern;
+use render::{render_coloured, ColourMode};
+use search::count_per_word;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Direction {
     LeftToRight,
     RightToLeft,
@@ -35,12 +49,30 @@ impl Direction {
         ];
         DIRECTIONS.iter()
     }
+
+    /// Position of the direction in `Direction::iterator`.
+    pub fn order(&self) -> usize {
+        Direction::iterator()
+            .position(|direction| direction == self)
+            .expect("Direction is listed")
+    }
+
+    pub fn reversed(&self) -> Direction {
+        match self {
+            LeftToRight => RightToLeft,
+            RightToLeft => LeftToRight,
+            TopToBottom => BottomToTop,
+            BottomToTop => TopToBottom,
+            NorthWestToSouthEast => SouthEastToNorthWest,
+            SouthEastToNorthWest => NorthWestToSouthEast,
+            NorthEastToSouthWest => SouthWestToNorthEast,
+            SouthWestToNorthEast => NorthEastToSouthWest,
+        }
+    }
 }
 
 #[derive(Debug)]
 struct Point {
-    x: isize,
-    y: isize,
     value: char,
     used: bool,
 }
@@ -50,10 +82,12 @@ struct GameMap {
     points: Vec<Point>,
     max_line: usize,
     xmas_count: usize,
+    /// Whether the grid is a torus, with each edge joined to the opposite one.
+    wrap: bool,
 }
 
 fn get_index(x: usize, y: usize, max_y: usize) -> usize {
-    return (max_y) * y + x;
+    (max_y) * y + x
 }
 
 fn valid_position(x: isize, y: isize, max: isize) -> bool {
@@ -74,6 +108,16 @@ fn next_x_y(x: isize, y: isize, step: isize, direction: &Direction) -> (isize, i
 }
 
 impl GameMap {
+    /// Grid cell at `x`, `y`, wrapping around the edges on a torus and
+    /// `None` outside a bounded grid.
+    fn resolve(&self, x: isize, y: isize) -> Option<(usize, usize)> {
+        let max = self.max_line as isize;
+        if self.wrap {
+            return Some((x.rem_euclid(max) as usize, y.rem_euclid(max) as usize));
+        }
+        valid_position(x, y, max).then_some((x as usize, y as usize))
+    }
+
     fn mark_point_used(&mut self, x: usize, y: usize) {
         self.points
             .get_mut(get_index(x, y, self.max_line))
@@ -100,11 +144,11 @@ impl Display for GameMap {
         for line in self.points.chunks_exact(self.max_line) {
             for point in line {
                 match point.used {
-                    true => str_out.push_str(&format!("{}", point.value)),
-                    false => str_out.push_str(&format!("{}", ".")),
+                    true => str_out.push(point.value),
+                    false => str_out.push('.'),
                 }
             }
-            str_out.push_str("\n");
+            str_out.push('\n');
         }
         writeln!(f, "{}", str_out)
     }
@@ -116,96 +160,176 @@ fn build_game_map(file_name: &str) -> GameMap {
     input_file
         .read_to_string(&mut content)
         .expect("Can read file");
-    let line_length = content.clone().lines().next().expect("Has line").len();
+    parse_game_map(&content)
+}
+
+fn parse_game_map(content: &str) -> GameMap {
+    let line_length = content.lines().next().expect("Has line").len();
     let mut points: Vec<Point> = Vec::new();
-    content
-        .split_ascii_whitespace()
-        .enumerate()
-        .for_each(|(y_idx, line)| {
-            line.chars().enumerate().for_each(|(x_idx, c)| {
-                points.push(Point {
-                    x: x_idx as isize,
-                    y: y_idx as isize,
-                    value: c,
-                    used: false,
-                });
+    content.split_ascii_whitespace().for_each(|line| {
+        line.chars().for_each(|c| {
+            points.push(Point {
+                value: c,
+                used: false,
             });
         });
+    });
     GameMap {
         points,
         max_line: line_length,
         xmas_count: 0,
+        wrap: false,
     }
 }
 
-fn main() {
-    let file_name = "input";
-
-    let mut map = build_game_map(&file_name);
-
-    for direction in Direction::iterator() {
-        for y in 0..map.max_line {
-            'XY: for x in 0..map.max_line {
-                let mut points_to_mark: Vec<(usize, usize)> = Vec::new();
-                for (idx, c) in "XMAS".chars().enumerate() {
-                    let (new_x, new_y) = next_x_y(x as isize, y as isize, idx as isize, direction);
-                    // println!(
-                    //     "{},{} is {}",
-                    //     new_x,
-                    //     new_y,
-                    //     valid_position(new_x, new_y, map.max_line as isize)
-                    // );
-                    if !valid_position(new_x, new_y, map.max_line as isize)
-                        || *map.get_char(new_x, new_y) != c
-                    {
-                        continue 'XY;
-                    }
-                    points_to_mark.push((new_x.try_into().unwrap(), new_y.try_into().unwrap()));
-                }
-                for (mark_x, mark_y) in points_to_mark {
-                    map.mark_point_used(mark_x, mark_y);
-                }
-                map.xmas_count += 1;
+struct Options {
+    file_name: String,
+    words: Vec<String>,
+    patterns: Vec<Pattern>,
+    list_matches: bool,
+    colour: Option<ColourMode>,
+    wrap: bool,
+    overlap: OverlapPolicy,
+    json_file: Option<String>,
+    csv_file: Option<String>,
+}
+
+/// Reads `[--input FILE] [--word-list FILE] [--pattern SPEC] [--list]
+/// [--colour match|count|never] [--wrap] [--overlap allow|forbid]
+/// [--json FILE] [--csv FILE] [WORD...]`, searching for "XMAS" and the X-MAS
+/// pattern in "input" when nothing else is given. A pattern spec is `x-mas`,
+/// `plus-mas` or a stencil such as `M.S/.A./M.S`.
+fn parse_options() -> Options {
+    let mut options = Options {
+        file_name: "input".to_string(),
+        words: Vec::new(),
+        patterns: Vec::new(),
+        list_matches: false,
+        colour: ColourMode::auto(),
+        wrap: false,
+        overlap: OverlapPolicy::Allow,
+        json_file: None,
+        csv_file: None,
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => options.file_name = args.next().expect("--input needs a file name"),
+            "--word-list" => {
+                let list_name = args.next().expect("--word-list needs a file name");
+                let mut list_file = File::open(list_name).expect("Can open word list");
+                let mut content = String::new();
+                list_file
+                    .read_to_string(&mut content)
+                    .expect("Can read word list");
+                options
+                    .words
+                    .extend(content.split_ascii_whitespace().map(String::from));
+            }
+            "--pattern" => options.patterns.push(Pattern::from_spec(
+                &args.next().expect("--pattern needs a pattern"),
+            )),
+            "--list" => options.list_matches = true,
+            "--json" => options.json_file = Some(args.next().expect("--json needs a file name")),
+            "--csv" => options.csv_file = Some(args.next().expect("--csv needs a file name")),
+            "--wrap" => options.wrap = true,
+            "--overlap" => {
+                options.overlap =
+                    OverlapPolicy::from_spec(&args.next().expect("--overlap needs a policy"))
+            }
+            "--colour" => {
+                options.colour = ColourMode::from_spec(&args.next().expect("--colour needs a mode"))
             }
+            _ => options.words.push(arg),
         }
     }
+    if options.words.is_empty() {
+        options.words.push("XMAS".to_string());
+    }
+    if options.patterns.is_empty() {
+        options.patterns.push(Pattern::x_mas());
+    }
+    options
+}
+
+/// Prints the map coloured by match when a colour mode is set, otherwise
+/// falls back to the plain `Display` output.
+fn print_map(map: &GameMap, matches: &[Vec<(usize, usize)>], colour: Option<ColourMode>) {
+    match colour {
+        Some(mode) => println!("{}", render_coloured(map, matches, mode)),
+        None => println!("{}", map),
+    }
+}
+
+fn main() {
+    let options = parse_options();
+    let file_name = options.file_name.as_str();
 
-    println!("{}", map);
+    let mut map = build_game_map(file_name);
+    map.wrap = options.wrap;
+    let mut matches = map.find_words(&options.words);
+    let word_cells: Vec<_> = matches.iter().map(|m| m.cells.clone()).collect();
+    let keep = options.overlap.select(&word_cells);
+    matches = keep.into_iter().map(|idx| matches[idx].clone()).collect();
+    for word_match in &matches {
+        map.mark_match(word_match);
+    }
+    map.xmas_count = matches.len();
+
+    let match_cells: Vec<_> = matches.iter().map(|m| m.cells.clone()).collect();
+    print_map(&map, &match_cells, options.colour);
+    if options.list_matches {
+        for word_match in &matches {
+            println!("{}", word_match);
+        }
+    }
+    if options.words.len() > 1 {
+        for (word, count) in count_per_word(&options.words, &matches) {
+            println!("{}: {}", word, count);
+        }
+    }
     println!("Part one: {}", map.xmas_count);
 
-    let mut map = build_game_map(&file_name);
-    for y in 1..map.max_line - 1 {
-        for x in 1..map.max_line - 1 {
-            let mut points_to_mark: Vec<(usize, usize)> = Vec::new();
-            if *map.get_char(x as isize, y as isize) == 'A' {
-                if (*map.get_char((x - 1) as isize, (y - 1) as isize) == 'M'
-                    && *map.get_char((x + 1) as isize, (y + 1) as isize) == 'S'
-                    && *map.get_char((x - 1) as isize, (y + 1) as isize) == 'M'
-                    && *map.get_char((x + 1) as isize, (y - 1) as isize) == 'S')
-                    || (*map.get_char((x - 1) as isize, (y - 1) as isize) == 'M'
-                        && *map.get_char((x + 1) as isize, (y + 1) as isize) == 'S'
-                        && *map.get_char((x - 1) as isize, (y + 1) as isize) == 'S'
-                        && *map.get_char((x + 1) as isize, (y - 1) as isize) == 'M')
-                    || (*map.get_char((x - 1) as isize, (y - 1) as isize) == 'S'
-                        && *map.get_char((x + 1) as isize, (y + 1) as isize) == 'M'
-                        && *map.get_char((x - 1) as isize, (y + 1) as isize) == 'S'
-                        && *map.get_char((x + 1) as isize, (y - 1) as isize) == 'M')
-                    || (*map.get_char((x - 1) as isize, (y - 1) as isize) == 'S'
-                        && *map.get_char((x + 1) as isize, (y + 1) as isize) == 'M'
-                        && *map.get_char((x - 1) as isize, (y + 1) as isize) == 'M'
-                        && *map.get_char((x + 1) as isize, (y - 1) as isize) == 'S')
-                {
-                    map.mark_point_used(x - 1, y - 1);
-                    map.mark_point_used(x - 1, y + 1);
-                    map.mark_point_used(x + 1, y + 1);
-                    map.mark_point_used(x + 1, y - 1);
-                    map.mark_point_used(x, y);
-
-                    map.xmas_count += 1;
-                }
-            }
+    let mut map = build_game_map(file_name);
+    map.wrap = options.wrap;
+    let mut pattern_matches: Vec<_> = options
+        .patterns
+        .iter()
+        .flat_map(|pattern| map.find_pattern(pattern))
+        .collect();
+    let pattern_cells: Vec<_> = pattern_matches.iter().map(|m| m.cells.clone()).collect();
+    let keep = options.overlap.select(&pattern_cells);
+    pattern_matches = keep
+        .into_iter()
+        .map(|idx| pattern_matches[idx].clone())
+        .collect();
+    for pattern_match in &pattern_matches {
+        map.mark_pattern_match(pattern_match);
+    }
+    map.xmas_count = pattern_matches.len();
+
+    let match_cells: Vec<_> = pattern_matches.iter().map(|m| m.cells.clone()).collect();
+    print_map(&map, &match_cells, options.colour);
+    if options.list_matches {
+        for pattern_match in &pattern_matches {
+            println!("{}", pattern_match);
+        }
+    }
+    if options.patterns.len() > 1 {
+        for pattern in &options.patterns {
+            let count = pattern_matches
+                .iter()
+                .filter(|m| m.pattern == pattern.name)
+                .count();
+            println!("{}: {}", pattern.name, count);
         }
     }
-    println!("{}", map);
     println!("Part two: {}", map.xmas_count);
+
+    if let Some(json_file) = &options.json_file {
+        fs::write(json_file, export::to_json(&matches, &pattern_matches)).expect("Can write JSON");
+    }
+    if let Some(csv_file) = &options.csv_file {
+        fs::write(csv_file, export::to_csv(&matches, &pattern_matches)).expect("Can write CSV");
+    }
 }
@@ -0,0 +1,145 @@
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlapPolicy {
+    /// Matches may share letters, every match counts.
+    Allow,
+    /// No letter may be used by two matches. The largest possible set of
+    /// matches is kept.
+    Forbid,
+}
+
+impl OverlapPolicy {
+    pub fn from_spec(spec: &str) -> OverlapPolicy {
+        match spec {
+            "allow" => OverlapPolicy::Allow,
+            "forbid" => OverlapPolicy::Forbid,
+            _ => panic!("Unknown overlap policy {}", spec),
+        }
+    }
+
+    /// Indices of the matches to keep under this policy.
+    pub fn select(&self, matches: &[Vec<(usize, usize)>]) -> Vec<usize> {
+        match self {
+            OverlapPolicy::Allow => (0..matches.len()).collect(),
+            OverlapPolicy::Forbid => select_non_overlapping(matches),
+        }
+    }
+}
+
+/// Picks a maximum set of matches that share no cells, returned in
+/// ascending order. This is a maximum independent set on the graph of
+/// overlapping matches, solved exactly per connected component.
+pub fn select_non_overlapping(matches: &[Vec<(usize, usize)>]) -> Vec<usize> {
+    let mut by_cell: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (id, cells) in matches.iter().enumerate() {
+        for cell in cells {
+            by_cell.entry(*cell).or_default().push(id);
+        }
+    }
+    // A match that covers the same cell twice (possible on a wrapping grid)
+    // does not conflict with itself.
+    let mut neighbours: Vec<HashSet<usize>> = vec![HashSet::new(); matches.len()];
+    for ids in by_cell.values() {
+        for a in ids {
+            for b in ids {
+                if a != b {
+                    neighbours[*a].insert(*b);
+                }
+            }
+        }
+    }
+    let mut selected = Vec::new();
+    let mut seen = vec![false; matches.len()];
+    for root in 0..matches.len() {
+        if seen[root] {
+            continue;
+        }
+        let mut component = vec![root];
+        seen[root] = true;
+        let mut idx = 0;
+        while idx < component.len() {
+            for next in &neighbours[component[idx]] {
+                if !seen[*next] {
+                    seen[*next] = true;
+                    component.push(*next);
+                }
+            }
+            idx += 1;
+        }
+        component.sort();
+        let mut best = Vec::new();
+        maximum_independent_set(&neighbours, component, &mut Vec::new(), &mut best);
+        selected.extend(best);
+    }
+    selected.sort();
+    selected
+}
+
+/// Branch and bound: some maximum set contains either the candidate with the
+/// fewest conflicts or one of its neighbours, so only those are branched on.
+fn maximum_independent_set(
+    neighbours: &[HashSet<usize>],
+    candidates: Vec<usize>,
+    current: &mut Vec<usize>,
+    best: &mut Vec<usize>,
+) {
+    if current.len() + candidates.len() <= best.len() {
+        return;
+    }
+    let degree = |id: &usize| {
+        neighbours[*id]
+            .iter()
+            .filter(|n| candidates.contains(n))
+            .count()
+    };
+    let Some(pivot) = candidates.iter().min_by_key(|id| degree(id)).copied() else {
+        *best = current.clone();
+        return;
+    };
+    let mut branches = vec![pivot];
+    if degree(&pivot) > 1 {
+        branches.extend(
+            candidates
+                .iter()
+                .filter(|id| neighbours[pivot].contains(id)),
+        );
+    }
+    for chosen in branches {
+        let remaining: Vec<usize> = candidates
+            .iter()
+            .filter(|id| **id != chosen && !neighbours[chosen].contains(id))
+            .copied()
+            .collect();
+        current.push(chosen);
+        maximum_independent_set(neighbours, remaining, current, best);
+        current.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain_keeps_both_ends() {
+        let matches = vec![
+            vec![(0, 0), (1, 0)],
+            vec![(1, 0), (2, 0)],
+            vec![(2, 0), (3, 0)],
+        ];
+        assert_eq!(vec![0, 2], select_non_overlapping(&matches));
+    }
+
+    #[test]
+    fn star_drops_the_centre() {
+        let matches = vec![
+            vec![(1, 0), (1, 1), (1, 2)],
+            vec![(0, 0), (1, 0)],
+            vec![(0, 1), (1, 1)],
+            vec![(0, 2), (1, 2)],
+            vec![(5, 5)],
+        ];
+        assert_eq!(vec![1, 2, 3, 4], select_non_overlapping(&matches));
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt::{Display, Formatter, Result};
+
+use crate::GameMap;
+
+/// Letters of a stencil as offsets from its top left corner.
+type Stencil = Vec<(isize, isize, char)>;
+
+/// A small 2D stencil of letters. Cells written as `.` are wildcards that
+/// match anything. Rows are separated by newlines or `/`.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub name: String,
+    cells: Stencil,
+}
+
+/// Quarter turns clockwise, applied after an optional mirror along the
+/// vertical axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Orientation {
+    pub rotation: usize,
+    pub reflected: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternMatch {
+    pub pattern: String,
+    pub x: usize,
+    pub y: usize,
+    pub orientation: Orientation,
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl Display for Orientation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "rotated {}", self.rotation * 90)?;
+        if self.reflected {
+            write!(f, ", reflected")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for PatternMatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "{} at {},{} {}",
+            self.pattern, self.x, self.y, self.orientation
+        )
+    }
+}
+
+impl Pattern {
+    pub fn parse(name: &str, stencil: &str) -> Pattern {
+        let mut cells = Vec::new();
+        for (y, row) in stencil.split(['\n', '/']).enumerate() {
+            for (x, c) in row.trim().chars().enumerate() {
+                if c != '.' {
+                    cells.push((x as isize, y as isize, c));
+                }
+            }
+        }
+        Pattern {
+            name: name.to_string(),
+            cells,
+        }
+    }
+
+    pub fn x_mas() -> Pattern {
+        Pattern::parse("X-MAS", "M.S/.A./M.S")
+    }
+
+    pub fn plus_mas() -> Pattern {
+        Pattern::parse("+-MAS", ".M./MAS/.S.")
+    }
+
+    /// Looks up a built-in pattern by name, otherwise reads `spec` as a stencil.
+    pub fn from_spec(spec: &str) -> Pattern {
+        match spec {
+            "x-mas" => Pattern::x_mas(),
+            "plus-mas" => Pattern::plus_mas(),
+            _ => Pattern::parse(spec, spec),
+        }
+    }
+
+    /// All distinct rotations and reflections of the stencil. Cells are moved
+    /// so the top left corner of the bounding box is at 0,0 and sorted, which
+    /// makes symmetric orientations compare equal.
+    pub fn orientations(&self) -> Vec<(Orientation, Stencil)> {
+        let mut orientations: Vec<(Orientation, Stencil)> = Vec::new();
+        for reflected in [false, true] {
+            for rotation in 0..4 {
+                let mut cells: Stencil = self
+                    .cells
+                    .iter()
+                    .map(|&(x, y, c)| {
+                        let x = if reflected { -x } else { x };
+                        let (x, y) = (0..rotation).fold((x, y), |(x, y), _| (-y, x));
+                        (x, y, c)
+                    })
+                    .collect();
+                let min_x = cells.iter().map(|(x, _, _)| *x).min().unwrap_or(0);
+                let min_y = cells.iter().map(|(_, y, _)| *y).min().unwrap_or(0);
+                cells
+                    .iter_mut()
+                    .for_each(|(x, y, _)| (*x, *y) = (*x - min_x, *y - min_y));
+                cells.sort();
+                if !orientations.iter().any(|(_, other)| *other == cells) {
+                    orientations.push((
+                        Orientation {
+                            rotation,
+                            reflected,
+                        },
+                        cells,
+                    ));
+                }
+            }
+        }
+        orientations
+    }
+}
+
+impl GameMap {
+    /// Finds every placement of `pattern` in every distinct orientation. The
+    /// reported position is the top left corner of the stencil's bounding box.
+    /// On a wrapping grid stencils may cross the edges.
+    pub fn find_pattern(&self, pattern: &Pattern) -> Vec<PatternMatch> {
+        let max = self.max_line as isize;
+        let mut matches = Vec::new();
+        for (orientation, cells) in pattern.orientations() {
+            for y in 0..max {
+                for x in 0..max {
+                    let covered: Option<Vec<_>> = cells
+                        .iter()
+                        .map(|(dx, dy, c)| {
+                            self.resolve(x + dx, y + dy).filter(|(cell_x, cell_y)| {
+                                *self.get_char(*cell_x as isize, *cell_y as isize) == *c
+                            })
+                        })
+                        .collect();
+                    if let Some(covered) = covered {
+                        matches.push(PatternMatch {
+                            pattern: pattern.name.clone(),
+                            x: x as usize,
+                            y: y as usize,
+                            orientation,
+                            cells: covered,
+                        });
+                    }
+                }
+            }
+        }
+        matches
+    }
+
+    pub fn mark_pattern_match(&mut self, pattern_match: &PatternMatch) {
+        for (x, y) in &pattern_match.cells {
+            self.mark_point_used(*x, *y);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_game_map, parse_game_map};
+
+    #[test]
+    fn example_x_mas() {
+        let map = build_game_map("example");
+        assert_eq!(9, map.find_pattern(&Pattern::x_mas()).len());
+    }
+
+    #[test]
+    fn patterns_wrap_around_edges() {
+        let mut map = parse_game_map("ABCD\nESFM\nGHIJ\nKSLM");
+        assert!(map.find_pattern(&Pattern::x_mas()).is_empty());
+        map.wrap = true;
+        let matches = map.find_pattern(&Pattern::x_mas());
+        assert_eq!(1, matches.len());
+        assert_eq!((3, 3), (matches[0].x, matches[0].y));
+    }
+
+    #[test]
+    fn symmetric_orientations_are_merged() {
+        assert_eq!(4, Pattern::x_mas().orientations().len());
+        assert_eq!(4, Pattern::plus_mas().orientations().len());
+        assert_eq!(1, Pattern::parse("square", "AA/AA").orientations().len());
+        assert_eq!(8, Pattern::parse("L", "X./XM/.A").orientations().len());
+    }
+}
@@ -0,0 +1,87 @@
+use std::io::{stdout, IsTerminal};
+
+use crate::{get_index, GameMap};
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+const MATCH_COLOURS: [&str; 12] = [
+    "\x1b[31m", "\x1b[32m", "\x1b[33m", "\x1b[34m", "\x1b[35m", "\x1b[36m", "\x1b[91m", "\x1b[92m",
+    "\x1b[93m", "\x1b[94m", "\x1b[95m", "\x1b[96m",
+];
+/// Used once, twice, three times and four or more times.
+const COUNT_COLOURS: [&str; 4] = ["\x1b[32m", "\x1b[33m", "\x1b[35m", "\x1b[1;31m"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColourMode {
+    /// Each match gets its own colour. Letters shared by several matches are
+    /// coloured by how often they are used instead.
+    Match,
+    /// Every used letter is coloured by how often it is used.
+    Count,
+}
+
+impl ColourMode {
+    pub fn from_spec(spec: &str) -> Option<ColourMode> {
+        match spec {
+            "match" => Some(ColourMode::Match),
+            "count" => Some(ColourMode::Count),
+            "never" => None,
+            _ => panic!("Unknown colour mode {}", spec),
+        }
+    }
+
+    /// Colour by match, unless stdout is not a terminal.
+    pub fn auto() -> Option<ColourMode> {
+        stdout().is_terminal().then_some(ColourMode::Match)
+    }
+}
+
+fn count_colour(count: usize) -> &'static str {
+    COUNT_COLOURS[count.min(COUNT_COLOURS.len()) - 1]
+}
+
+/// Renders the full grid with ANSI colours. `matches` holds the cells of
+/// each match; letters not used by any match are dimmed.
+pub fn render_coloured(map: &GameMap, matches: &[Vec<(usize, usize)>], mode: ColourMode) -> String {
+    let mut owners: Vec<Vec<usize>> = vec![Vec::new(); map.points.len()];
+    for (id, cells) in matches.iter().enumerate() {
+        for (x, y) in cells {
+            owners[get_index(*x, *y, map.max_line)].push(id);
+        }
+    }
+    let mut out = String::new();
+    for (line, line_owners) in map
+        .points
+        .chunks_exact(map.max_line)
+        .zip(owners.chunks_exact(map.max_line))
+    {
+        for (point, point_owners) in line.iter().zip(line_owners) {
+            let colour = match (mode, point_owners.as_slice()) {
+                (_, []) => DIM,
+                (ColourMode::Match, [id]) => MATCH_COLOURS[id % MATCH_COLOURS.len()],
+                (_, shared) => count_colour(shared.len()),
+            };
+            out.push_str(colour);
+            out.push(point.value);
+            out.push_str(RESET);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build_game_map;
+
+    #[test]
+    fn shared_letters_use_count_colour() {
+        let map = build_game_map("example");
+        let matches = vec![vec![(0, 0), (1, 0)], vec![(1, 0), (2, 0)]];
+        let rendered = render_coloured(&map, &matches, ColourMode::Match);
+        let first_line = rendered.lines().next().expect("Has line");
+        assert!(first_line.starts_with("\x1b[31mM\x1b[0m\x1b[33mM\x1b[0m\x1b[32mM\x1b[0m"));
+        assert!(first_line.ends_with("\x1b[2mM\x1b[0m"));
+    }
+}
@@ -0,0 +1,244 @@
+use std::fmt::{Display, Formatter, Result};
+
+use crate::{automaton::Automaton, next_x_y, Direction, GameMap};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordMatch {
+    pub word: String,
+    pub x: usize,
+    pub y: usize,
+    pub direction: Direction,
+    /// Every grid cell covered by the match, starting with the first letter.
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl Display for WordMatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "{} at {},{} {:?}",
+            self.word, self.x, self.y, self.direction
+        )
+    }
+}
+
+impl GameMap {
+    #[cfg(test)]
+    fn cells_at(
+        &self,
+        word: &str,
+        x: usize,
+        y: usize,
+        direction: &Direction,
+    ) -> Option<Vec<(usize, usize)>> {
+        word.chars()
+            .enumerate()
+            .map(|(idx, c)| {
+                let (new_x, new_y) = next_x_y(x as isize, y as isize, idx as isize, direction);
+                self.resolve(new_x, new_y)
+                    .filter(|(x, y)| *self.get_char(*x as isize, *y as isize) == c)
+            })
+            .collect()
+    }
+
+    /// Finds every occurrence of `word` in all eight directions by checking
+    /// every start cell. Single letter words read the same in every direction,
+    /// so they are only reported once. Kept as the reference for `find_words`.
+    #[cfg(test)]
+    pub fn find_word(&self, word: &str) -> Vec<WordMatch> {
+        let directions: Vec<&Direction> = match word.chars().count() {
+            0 => return Vec::new(),
+            1 => vec![&Direction::LeftToRight],
+            _ => Direction::iterator().collect(),
+        };
+        let mut matches = Vec::new();
+        for direction in directions {
+            for y in 0..self.max_line {
+                for x in 0..self.max_line {
+                    if let Some(cells) = self.cells_at(word, x, y, direction) {
+                        matches.push(WordMatch {
+                            word: word.to_string(),
+                            x,
+                            y,
+                            direction: *direction,
+                            cells,
+                        });
+                    }
+                }
+            }
+        }
+        matches
+    }
+
+    /// Every row, column and diagonal of the grid, read in the first of its
+    /// two directions. The other direction is covered by searching for the
+    /// reversed words. On a wrapping grid every line is a cycle of
+    /// `max_line` cells, so the diagonals all start on the top row.
+    fn lines(&self) -> Vec<(Direction, Vec<(usize, usize)>)> {
+        let last = self.max_line - 1;
+        let mut starts: Vec<(Direction, usize, usize)> = Vec::new();
+        for i in 0..self.max_line {
+            starts.push((Direction::LeftToRight, 0, i));
+            starts.push((Direction::TopToBottom, i, 0));
+            starts.push((Direction::NorthWestToSouthEast, i, 0));
+            starts.push((Direction::NorthEastToSouthWest, i, 0));
+            if i > 0 && !self.wrap {
+                starts.push((Direction::NorthWestToSouthEast, 0, i));
+                starts.push((Direction::NorthEastToSouthWest, last, i));
+            }
+        }
+        starts
+            .into_iter()
+            .map(|(direction, x, y)| {
+                let cells = (0..self.max_line as isize)
+                    .map(|step| next_x_y(x as isize, y as isize, step, &direction))
+                    .map_while(|(x, y)| self.resolve(x, y))
+                    .collect();
+                (direction, cells)
+            })
+            .collect()
+    }
+
+    /// Finds every occurrence of all `words` in all eight directions by
+    /// scanning each line of the grid once with an Aho-Corasick automaton.
+    /// Matches come out in the same order as calling `find_word` per word.
+    pub fn find_words(&self, words: &[String]) -> Vec<WordMatch> {
+        // Pattern 2 * i is word i, pattern 2 * i + 1 is word i reversed.
+        let automaton = Automaton::new(words.iter().flat_map(|word| {
+            let reversed: Vec<char> = word.chars().rev().collect();
+            [word.chars().collect::<Vec<_>>(), reversed]
+        }));
+        let longest = words.iter().map(|w| w.chars().count()).max().unwrap_or(0);
+        let mut matches = Vec::new();
+        for (direction, cells) in self.lines() {
+            // A cyclic line is read around once more so that words crossing
+            // the edge are seen, but only windows starting in the first lap
+            // are kept.
+            let text_len = match self.wrap {
+                true => cells.len() + longest.max(1) - 1,
+                false => cells.len(),
+            };
+            let text = (0..text_len).map(|idx| {
+                let (x, y) = cells[idx % cells.len()];
+                *self.get_char(x as isize, y as isize)
+            });
+            automaton.scan(text, |pattern, end| {
+                let length = automaton.pattern_len(pattern);
+                let start = end + 1 - length;
+                let reversed = pattern % 2 == 1;
+                if start >= cells.len()
+                    || (length == 1 && (reversed || direction != Direction::LeftToRight))
+                {
+                    return;
+                }
+                let mut covered: Vec<_> =
+                    (start..=end).map(|idx| cells[idx % cells.len()]).collect();
+                let direction = match reversed {
+                    true => {
+                        covered.reverse();
+                        direction.reversed()
+                    }
+                    false => direction,
+                };
+                matches.push((
+                    pattern / 2,
+                    WordMatch {
+                        word: words[pattern / 2].clone(),
+                        x: covered[0].0,
+                        y: covered[0].1,
+                        direction,
+                        cells: covered,
+                    },
+                ));
+            });
+        }
+        matches.sort_by_key(|(word, m)| (*word, m.direction.order(), m.y, m.x));
+        matches.into_iter().map(|(_, m)| m).collect()
+    }
+
+    pub fn mark_match(&mut self, word_match: &WordMatch) {
+        for (x, y) in &word_match.cells {
+            self.mark_point_used(*x, *y);
+        }
+    }
+}
+
+/// Number of matches per word, in the order the words were given.
+pub fn count_per_word<'a>(words: &'a [String], matches: &[WordMatch]) -> Vec<(&'a str, usize)> {
+    words
+        .iter()
+        .map(|word| {
+            let count = matches.iter().filter(|m| m.word == *word).count();
+            (word.as_str(), count)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_game_map, parse_game_map};
+
+    #[test]
+    fn example_xmas() {
+        let map = build_game_map("example");
+        assert_eq!(18, map.find_word("XMAS").len());
+    }
+
+    #[test]
+    fn match_cells_follow_direction() {
+        let map = build_game_map("example");
+        let word_match = map
+            .find_words(&["XMAS".to_string()])
+            .into_iter()
+            .find(|m| m.direction == Direction::SouthEastToNorthWest)
+            .expect("Has diagonal match");
+        assert_eq!((6, 5), (word_match.x, word_match.y));
+        assert_eq!(vec![(6, 5), (5, 4), (4, 3), (3, 2)], word_match.cells);
+    }
+
+    #[test]
+    fn words_wrap_around_edges() {
+        let mut map = parse_game_map("MASX\nABCD\nEFGH\nIJKL");
+        assert!(map.find_words(&["XMAS".to_string()]).is_empty());
+        map.wrap = true;
+        let matches = map.find_words(&["XMAS".to_string(), "SAMX".to_string()]);
+        assert_eq!(2, matches.len());
+        assert_eq!(vec![(3, 0), (0, 0), (1, 0), (2, 0)], matches[0].cells);
+        assert_eq!(Direction::RightToLeft, matches[1].direction);
+    }
+
+    #[test]
+    fn automaton_agrees_with_direct_search() {
+        let map = build_game_map("input");
+        let words: Vec<String> = ["XMAS", "MAS", "SAS", "X", "AMAMA", "MXM", "XMASXMAS"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let direct: Vec<WordMatch> = words.iter().flat_map(|w| map.find_word(w)).collect();
+        assert_eq!(direct, map.find_words(&words));
+    }
+
+    #[test]
+    fn automaton_agrees_with_direct_search_on_torus() {
+        let mut map = build_game_map("example");
+        map.wrap = true;
+        let words: Vec<String> = ["XMAS", "MAS", "X", "MMMSXXMASMM", "SAMXMASXM"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let direct: Vec<WordMatch> = words.iter().flat_map(|w| map.find_word(w)).collect();
+        assert_eq!(direct, map.find_words(&words));
+    }
+
+    #[test]
+    fn counts_each_word() {
+        let map = build_game_map("example");
+        let words = vec!["XMAS".to_string(), "SAMX".to_string(), "A".to_string()];
+        let matches = map.find_words(&words);
+        assert_eq!(
+            vec![("XMAS", 18), ("SAMX", 18), ("A", 24)],
+            count_per_word(&words, &matches)
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use crate::{Rule, Update};
+
+/// The ordering rules as a directed graph, with an edge from the page that
+/// has to be printed first to the page that has to follow it.
+#[derive(Debug)]
+pub struct RuleGraph {
+    pub edges: BTreeMap<usize, BTreeSet<usize>>,
+}
+
+impl RuleGraph {
+    /// Builds the graph of all rules, or only of the rules between pages of
+    /// `update` when one is given.
+    pub fn new(rules: &[Rule], update: Option<&Update>) -> RuleGraph {
+        let mut edges: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+        if let Some(update) = update {
+            for page in &update.values {
+                edges.entry(*page).or_default();
+            }
+        }
+        for rule in rules {
+            let keep = update.is_none_or(|update| {
+                update.values.contains(&rule.before) && update.values.contains(&rule.after)
+            });
+            if keep {
+                edges.entry(rule.before).or_default().insert(rule.after);
+                edges.entry(rule.after).or_default();
+            }
+        }
+        RuleGraph { edges }
+    }
+
+    /// Shortest chain of pages leading from `from` to `to`, ignoring the
+    /// direct edge between them when `skip_direct` is set.
+    pub fn path(&self, from: usize, to: usize, skip_direct: bool) -> Option<Vec<usize>> {
+        let mut previous: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(page) = queue.pop_front() {
+            for next in self.edges.get(&page).into_iter().flatten() {
+                if (skip_direct && page == from && *next == to) || previous.contains_key(next) {
+                    continue;
+                }
+                previous.insert(*next, page);
+                if *next == to {
+                    let mut path = vec![to];
+                    let mut current = to;
+                    while current != from || path.len() == 1 {
+                        current = previous[&current];
+                        path.push(current);
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                queue.push_back(*next);
+            }
+        }
+        None
+    }
+
+    /// Removes every edge that is implied by a longer chain of other edges.
+    /// Edges are checked one at a time against the remaining graph, so pages
+    /// stay reachable from each other even when the rules contain cycles.
+    pub fn reduce(&mut self) {
+        let all_edges: Vec<(usize, usize)> = self
+            .edges
+            .iter()
+            .flat_map(|(before, afters)| afters.iter().map(|after| (*before, *after)))
+            .collect();
+        for (before, after) in all_edges {
+            if self.path(before, after, true).is_some() {
+                self.edges
+                    .get_mut(&before)
+                    .expect("Page exists")
+                    .remove(&after);
+            }
+        }
+    }
+
+    /// Edges that are part of a cycle, meaning the rules they belong to can
+    /// not all be satisfied by an update containing those pages.
+    pub fn cyclic_edges(&self) -> BTreeSet<(usize, usize)> {
+        self.edges
+            .iter()
+            .flat_map(|(before, afters)| afters.iter().map(|after| (*before, *after)))
+            .filter(|(before, after)| self.path(*after, *before, false).is_some())
+            .collect()
+    }
+
+    /// Graphviz DOT, with edges that are part of a cycle drawn in red.
+    pub fn to_dot(&self) -> String {
+        let cyclic = self.cyclic_edges();
+        let mut out = String::from("digraph rules {\n");
+        for (before, afters) in &self.edges {
+            out.push_str(&format!("    {};\n", before));
+            for after in afters {
+                match cyclic.contains(&(*before, *after)) {
+                    true => out.push_str(&format!("    {} -> {} [color=red];\n", before, after)),
+                    false => out.push_str(&format!("    {} -> {};\n", before, after)),
+                }
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Adjacency list keyed by page, plus the list of edges in cycles.
+    pub fn to_json(&self) -> String {
+        let adjacency: Vec<String> = self
+            .edges
+            .iter()
+            .map(|(before, afters)| {
+                let afters: Vec<String> = afters.iter().map(|after| after.to_string()).collect();
+                format!("\"{}\":[{}]", before, afters.join(","))
+            })
+            .collect();
+        let cyclic: Vec<String> = self
+            .cyclic_edges()
+            .iter()
+            .map(|(before, after)| format!("[{},{}]", before, after))
+            .collect();
+        format!(
+            "{{\"adjacency\":{{{}}},\"cyclic_edges\":[{}]}}\n",
+            adjacency.join(","),
+            cyclic.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules_from;
+
+    fn graph(pairs: &[(usize, usize)]) -> RuleGraph {
+        RuleGraph::new(&rules_from(pairs), None)
+    }
+
+    #[test]
+    fn reduction_drops_implied_edges() {
+        let mut rules = graph(&[(1, 2), (2, 3), (1, 3), (3, 4), (1, 4)]);
+        rules.reduce();
+        assert_eq!(
+            "{\"adjacency\":{\"1\":[2],\"2\":[3],\"3\":[4],\"4\":[]},\"cyclic_edges\":[]}\n",
+            rules.to_json()
+        );
+    }
+
+    #[test]
+    fn marks_cycles_in_dot() {
+        let rules = graph(&[(1, 2), (2, 1), (2, 3)]);
+        assert_eq!(
+            "digraph rules {\n    1;\n    1 -> 2 [color=red];\n    2;\n    2 -> 1 [color=red];\n    2 -> 3;\n    3;\n}\n",
+            rules.to_dot()
+        );
+    }
+}
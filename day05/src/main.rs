@@ -1,4 +1,20 @@
-use std::{fs::File, io::Read};
+mod graph;
+mod precedence;
+mod report;
+mod validate;
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    env,
+    fmt::{Display, Formatter, Result},
+    fs::File,
+    io::Read,
+    process,
+};
+
+use graph::RuleGraph;
+use precedence::Closure;
 
 #[derive(Debug, Clone)]
 struct Rule {
@@ -6,23 +22,37 @@ struct Rule {
     after: usize,
 }
 
-impl Rule {
-    fn applies(&self, update: &Update) -> bool {
-        update.values.contains(&self.before) && update.values.contains(&self.after)
+/// Rules written as `(before, after)` pairs.
+#[cfg(test)]
+fn rules_from(pairs: &[(usize, usize)]) -> Vec<Rule> {
+    pairs
+        .iter()
+        .map(|(before, after)| Rule {
+            before: *before,
+            after: *after,
+        })
+        .collect()
+}
+
+/// Rules grouped by the page that has to be printed first, so the rules for
+/// a page can be found without looking at all other rules.
+#[derive(Debug)]
+struct RuleIndex {
+    successors: HashMap<usize, Vec<usize>>,
+}
+
+impl RuleIndex {
+    fn new(rules: &[Rule]) -> RuleIndex {
+        let mut successors: HashMap<usize, Vec<usize>> = HashMap::new();
+        for rule in rules {
+            successors.entry(rule.before).or_default().push(rule.after);
+        }
+        RuleIndex { successors }
     }
 
-    fn complies(&self, update: &Update) -> bool {
-        let before_index = update
-                .values
-                .iter()
-                .position(|val| *val == self.before)
-                .expect("Before value exists in update");
-            let after_index = update
-                .values
-                .iter()
-                .position(|val| *val == self.after)
-                .expect("After value exists in update");
-            return before_index < after_index;
+    /// Pages that have to be printed after `page`.
+    fn successors(&self, page: usize) -> &[usize] {
+        self.successors.get(&page).map_or(&[], |pages| pages)
     }
 }
 
@@ -31,105 +61,303 @@ struct Update {
     values: Vec<usize>,
 }
 
+/// The rules that apply to an update require its pages to be printed in a
+/// circle, so no ordering can satisfy all of them.
+#[derive(Debug)]
+struct CycleError {
+    /// Pages in rule order, the first page must come after the last one.
+    pages: Vec<usize>,
+}
+
+impl Display for CycleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let pages: Vec<String> = self.pages.iter().map(|page| page.to_string()).collect();
+        write!(
+            f,
+            "rules form a cycle: {} -> {}",
+            pages.join(" -> "),
+            pages[0]
+        )
+    }
+}
+
 impl Update {
     fn get_middle_value(&self) -> usize {
         self.values[(self.values.len() - 1) / 2]
     }
 
-    fn complies_with_rules(&self, rules: &Vec<Rule>) -> bool {
-        rules.iter().all(|rule| self.complies_with_rule(rule))
+    /// Position of each page in the update.
+    fn positions(&self) -> HashMap<usize, usize> {
+        let mut positions = HashMap::new();
+        for (idx, page) in self.values.iter().enumerate() {
+            positions.entry(*page).or_insert(idx);
+        }
+        positions
     }
 
-    fn complies_with_rule(&self, rule: &Rule) -> bool {
-        if rule.applies(&self) {
-            return rule.complies(&self)
+    /// Positions `(before, after)` of the pages of every rule that applies to
+    /// this update.
+    fn applicable_rules(&self, index: &RuleIndex) -> Vec<(usize, usize)> {
+        let positions = self.positions();
+        let mut applicable = Vec::new();
+        for (before, page) in self.values.iter().enumerate() {
+            for after_page in index.successors(*page) {
+                if let Some(after) = positions.get(after_page) {
+                    applicable.push((before, *after));
+                }
+            }
         }
-        true
+        applicable
+    }
+
+    fn complies_with_rules(&self, index: &RuleIndex) -> bool {
+        self.applicable_rules(index)
+            .iter()
+            .all(|(before, after)| before < after)
     }
 
-    fn correct(&mut self, rules: &Vec<Rule>) -> &Update {
-        loop {
-            let mut rule_applied = false;
-            for rule in rules {
-                if rule.applies(&self) && !rule.complies(&self) {
-                    self.ensure_rule(rule);
-                    rule_applied = true;
+    /// Reorders the pages with a topological sort over the rules that apply
+    /// to this update. When several pages could come next, the one that was
+    /// earliest in the update goes first.
+    fn correct(&mut self, index: &RuleIndex) -> std::result::Result<&Update, CycleError> {
+        let mut successors: Vec<Vec<usize>> = vec![Vec::new(); self.values.len()];
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); self.values.len()];
+        for (before, after) in self.applicable_rules(index) {
+            successors[before].push(after);
+            predecessors[after].push(before);
+        }
+        let mut waiting_on: Vec<usize> = predecessors.iter().map(|p| p.len()).collect();
+        let mut ready: BinaryHeap<Reverse<usize>> = (0..self.values.len())
+            .filter(|idx| waiting_on[*idx] == 0)
+            .map(Reverse)
+            .collect();
+        let mut order: Vec<usize> = Vec::new();
+        while let Some(Reverse(idx)) = ready.pop() {
+            order.push(idx);
+            for next in &successors[idx] {
+                waiting_on[*next] -= 1;
+                if waiting_on[*next] == 0 {
+                    ready.push(Reverse(*next));
                 }
             }
-            if !rule_applied {
-                break;
+        }
+        if order.len() < self.values.len() {
+            let cycle = find_cycle(&predecessors, &waiting_on);
+            return Err(CycleError {
+                pages: cycle.iter().map(|idx| self.values[*idx]).collect(),
+            });
+        }
+        self.values = order.iter().map(|idx| self.values[*idx]).collect();
+        Ok(self)
+    }
+}
+
+/// Every page left unsorted still waits on another unsorted page, so walking
+/// backwards through those pages must eventually revisit one of them.
+fn find_cycle(predecessors: &[Vec<usize>], waiting_on: &[usize]) -> Vec<usize> {
+    let mut path: Vec<usize> = Vec::new();
+    let mut current = (0..waiting_on.len())
+        .find(|idx| waiting_on[*idx] > 0)
+        .expect("Unsorted page exists");
+    while !path.contains(&current) {
+        path.push(current);
+        current = *predecessors[current]
+            .iter()
+            .find(|idx| waiting_on[**idx] > 0)
+            .expect("Unsorted page waits on unsorted page");
+    }
+    let start = path
+        .iter()
+        .position(|idx| *idx == current)
+        .expect("In path");
+    let mut cycle = path.split_off(start);
+    cycle.reverse();
+    cycle
+}
+
+enum Command {
+    Solve,
+    Report,
+    Graph,
+    /// Must the first page be printed before the second?
+    Query(usize, usize),
+}
+
+struct Options {
+    file_name: String,
+    command: Command,
+    json: bool,
+    /// Restricts the graph to the pages of the update at this index.
+    update: Option<usize>,
+    reduce: bool,
+}
+
+/// Reads `[report | graph | query A B] [--json] [--update N] [--reduce]
+/// [--input FILE]`. Without a command both parts are solved for "input".
+/// `graph` writes DOT unless `--json` is given. `graph` and `query` only use
+/// the rules between pages of update N when `--update` is given.
+fn parse_options() -> Options {
+    let mut options = Options {
+        file_name: "input".to_string(),
+        command: Command::Solve,
+        json: false,
+        update: None,
+        reduce: false,
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => options.file_name = args.next().expect("--input needs a file name"),
+            "report" => options.command = Command::Report,
+            "graph" => options.command = Command::Graph,
+            "query" => {
+                let mut page = || -> usize {
+                    args.next()
+                        .expect("query needs two pages")
+                        .parse()
+                        .expect("Page is a number")
+                };
+                options.command = Command::Query(page(), page());
+            }
+            "--json" => options.json = true,
+            "--update" => {
+                options.update = Some(
+                    args.next()
+                        .expect("--update needs an index")
+                        .parse()
+                        .expect("Update index is a number"),
+                )
             }
+            "--reduce" => options.reduce = true,
+            _ => panic!("Unknown argument {}", arg),
         }
-        return self
-    }
-
-    fn ensure_rule(&mut self, rule: &Rule) -> &Self {
-        let move_index = self.values.iter().position(|val| *val == rule.before).expect("moveable value exists");
-        let move_to_index = self.values.iter().position(|val| *val == rule.after).expect("to move value exists");
-        let new_values = if move_to_index == 0 {
-            let mut temp: Vec<usize> = Vec::new();
-            temp.push(self.values[move_index]);
-            temp.append(&mut self.values.clone().iter().filter(|v| **v != rule.before).map(|a| *a).collect::<Vec<_>>());
-            temp
-        }else {
-            let mut temp: Vec<usize> = Vec::new();
-            self.values[0..move_to_index].iter().for_each(|v| temp.push(*v));
-            temp.push(self.values[move_index]);
-            temp.append(&mut self.values[move_to_index..].iter().filter(|v| **v != rule.before).map(|v| *v).collect());
-            temp
-        };
-        self.values = new_values;
-        self
     }
+    options
 }
 
 fn main() {
-    let file_name = "input";
+    let options = parse_options();
+    let file_name = options.file_name.as_str();
     let mut input_file = File::open(file_name).expect("Can open file");
     let mut content = String::new();
     input_file
         .read_to_string(&mut content)
         .expect("Can read file");
 
-    let mut split_content = content.split("\n\n");
-    let rules: Vec<Rule> = split_content
-        .next()
-        .expect("Rules exist")
-        .split("\n")
-        .map(|line| {
-            let mut parts = line.split("|");
-            let left = parts.next().expect("Has left part");
-            let right = parts.next().expect("has right part").trim();
-            Rule {
-                before: left.parse().expect("first part is number"),
-                after: right.parse().expect("second part is number"),
+    let input = validate::parse_input(&content);
+    for issue in &input.issues {
+        eprintln!("{}", issue);
+    }
+    if input.has_errors() {
+        process::exit(1);
+    }
+    let rules = input.rules;
+    let mut updates = input.updates;
+    let index = RuleIndex::new(&rules);
+
+    match options.command {
+        Command::Solve => {}
+        Command::Report => {
+            let reports = report::build_reports(&updates, &index);
+            match options.json {
+                true => print!("{}", report::to_json(&reports)),
+                false => print!("{}", report::to_text(&reports)),
             }
-        })
-        .collect();
-
-    let mut updates: Vec<Update> = split_content
-        .next()
-        .expect("Updates exists")
-        .split("\n")
-        .map(|line| Update {
-            values: line
-                .split(",")
-                .map(|number| number.parse().expect("Updates contain numbers"))
-                .collect(),
-        })
-        .collect();
+            return;
+        }
+        Command::Graph | Command::Query(_, _) => {
+            let update = options
+                .update
+                .map(|idx| updates.get(idx).expect("Update index exists"));
+            let mut graph = RuleGraph::new(&rules, update);
+            if options.reduce {
+                graph.reduce();
+            }
+            match (options.command, options.json) {
+                (Command::Query(first, second), _) => {
+                    println!("{}", Closure::new(&graph).precedence(first, second))
+                }
+                (_, true) => print!("{}", graph.to_json()),
+                (_, false) => print!("{}", graph.to_dot()),
+            }
+            return;
+        }
+    }
 
     let first_part_value: usize = updates
         .iter()
-        .filter(|update| update.complies_with_rules(&rules))
+        .filter(|update| update.complies_with_rules(&index))
         .map(|update| update.get_middle_value())
         .sum();
     println!("Part one: {}", first_part_value);
     let second_part_value: usize = updates
         .iter_mut()
-        .filter(|update| !update.complies_with_rules(&rules))
-        .map(|update| update.correct(&rules))
+        .filter(|update| !update.complies_with_rules(&index))
+        .map(|update| {
+            let pages = update.values.clone();
+            update.correct(&index).unwrap_or_else(|error| {
+                eprintln!("Cannot correct update {:?}: {}", pages, error);
+                process::exit(1);
+            })
+        })
         .map(|update| update.get_middle_value())
         .sum();
     println!("Part two: {}", second_part_value);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(pairs: &[(usize, usize)]) -> RuleIndex {
+        RuleIndex::new(&rules_from(pairs))
+    }
+
+    #[test]
+    fn corrects_example_updates() {
+        let rules = rules(&[
+            (47, 53),
+            (97, 13),
+            (97, 61),
+            (97, 47),
+            (75, 29),
+            (61, 13),
+            (75, 53),
+            (29, 13),
+            (97, 29),
+            (53, 29),
+            (61, 53),
+            (97, 53),
+            (61, 29),
+            (47, 13),
+            (75, 47),
+            (97, 75),
+            (47, 61),
+            (75, 61),
+            (47, 29),
+            (75, 13),
+            (53, 13),
+        ]);
+        let mut update = Update {
+            values: vec![97, 13, 75, 29, 47],
+        };
+        assert!(!update.complies_with_rules(&rules));
+        assert!(Update {
+            values: vec![75, 47, 61, 53, 29]
+        }
+        .complies_with_rules(&rules));
+        let corrected = update.correct(&rules).expect("No cycle");
+        assert_eq!(vec![97, 75, 47, 29, 13], corrected.values);
+    }
+
+    #[test]
+    fn reports_cycle() {
+        let rules = rules(&[(1, 2), (2, 3), (3, 1), (4, 1)]);
+        let mut update = Update {
+            values: vec![4, 3, 2, 1],
+        };
+        let error = update.correct(&rules).expect_err("Has cycle");
+        assert_eq!(vec![1, 2, 3], error.pages);
+        assert_eq!("rules form a cycle: 1 -> 2 -> 3 -> 1", error.to_string());
+    }
+}
@@ -0,0 +1,192 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::{Display, Formatter, Result},
+};
+
+use crate::graph::RuleGraph;
+
+/// Transitive closure of the rule graph. For every page it keeps the pages
+/// that have to follow it, each with the page it is reached from, so the
+/// chain of rules behind an answer can be rebuilt.
+#[derive(Debug)]
+pub struct Closure {
+    reached_from: BTreeMap<usize, BTreeMap<usize, usize>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Precedence {
+    /// The first page must come before the second, by this chain of pages.
+    Before(Vec<usize>),
+    /// The first page must come after the second, by this chain of pages
+    /// leading from the second page to the first.
+    After(Vec<usize>),
+    /// The rules require both orders, so the pages can not be printed
+    /// together.
+    Conflicting {
+        before: Vec<usize>,
+        after: Vec<usize>,
+    },
+    /// No chain of rules connects the two pages.
+    Unordered(usize, usize),
+    /// Both pages are the same page, with the cycle of rules that makes it
+    /// follow itself, if there is one.
+    Same {
+        page: usize,
+        cycle: Option<Vec<usize>>,
+    },
+}
+
+impl Closure {
+    pub fn new(graph: &RuleGraph) -> Closure {
+        let mut reached_from = BTreeMap::new();
+        for start in graph.edges.keys() {
+            let mut previous: BTreeMap<usize, usize> = BTreeMap::new();
+            let mut queue = VecDeque::from([*start]);
+            while let Some(page) = queue.pop_front() {
+                for next in &graph.edges[&page] {
+                    if !previous.contains_key(next) {
+                        previous.insert(*next, page);
+                        queue.push_back(*next);
+                    }
+                }
+            }
+            reached_from.insert(*start, previous);
+        }
+        Closure { reached_from }
+    }
+
+    /// Shortest chain of pages from `from` to `to`, if `to` has to follow.
+    fn chain(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let previous = self.reached_from.get(&from)?;
+        previous.get(&to)?;
+        let mut chain = vec![to];
+        let mut current = to;
+        while current != from || chain.len() == 1 {
+            current = previous[&current];
+            chain.push(current);
+        }
+        chain.reverse();
+        Some(chain)
+    }
+
+    pub fn precedence(&self, first: usize, second: usize) -> Precedence {
+        if first == second {
+            return Precedence::Same {
+                page: first,
+                cycle: self.chain(first, first),
+            };
+        }
+        match (self.chain(first, second), self.chain(second, first)) {
+            (Some(before), Some(after)) => Precedence::Conflicting { before, after },
+            (Some(before), None) => Precedence::Before(before),
+            (None, Some(after)) => Precedence::After(after),
+            (None, None) => Precedence::Unordered(first, second),
+        }
+    }
+}
+
+/// Rules along a chain of pages, written as `before|after`.
+fn rules(chain: &[usize]) -> String {
+    chain
+        .windows(2)
+        .map(|pair| format!("{}|{}", pair[0], pair[1]))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Display for Precedence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Precedence::Before(chain) => write!(
+                f,
+                "{} must be printed before {}: {}",
+                chain[0],
+                chain[chain.len() - 1],
+                rules(chain)
+            ),
+            Precedence::After(chain) => write!(
+                f,
+                "{} must be printed after {}: {}",
+                chain[chain.len() - 1],
+                chain[0],
+                rules(chain)
+            ),
+            Precedence::Conflicting { before, after } => write!(
+                f,
+                "{} and {} conflict: {} and {}",
+                before[0],
+                after[0],
+                rules(before),
+                rules(after)
+            ),
+            Precedence::Unordered(first, second) => {
+                write!(f, "{} and {} are unordered", first, second)
+            }
+            Precedence::Same { page, cycle: None } => write!(f, "{} is the same page", page),
+            Precedence::Same {
+                page,
+                cycle: Some(cycle),
+            } => write!(
+                f,
+                "{} is the same page, and has to be printed before itself: {}",
+                page,
+                rules(cycle)
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules_from;
+
+    fn closure(pairs: &[(usize, usize)]) -> Closure {
+        Closure::new(&RuleGraph::new(&rules_from(pairs), None))
+    }
+
+    #[test]
+    fn follows_chains_of_rules() {
+        let closure = closure(&[(1, 2), (2, 3), (3, 4), (5, 4)]);
+        assert_eq!(
+            Precedence::Before(vec![1, 2, 3, 4]),
+            closure.precedence(1, 4)
+        );
+        assert_eq!(Precedence::After(vec![2, 3]), closure.precedence(3, 2));
+        assert_eq!(Precedence::Unordered(1, 5), closure.precedence(1, 5));
+        assert_eq!(
+            "1 must be printed before 4: 1|2, 2|3, 3|4",
+            closure.precedence(1, 4).to_string()
+        );
+    }
+
+    #[test]
+    fn reports_conflicts() {
+        let closure = closure(&[(1, 2), (2, 3), (3, 1)]);
+        assert_eq!(
+            Precedence::Conflicting {
+                before: vec![1, 2],
+                after: vec![2, 3, 1]
+            },
+            closure.precedence(1, 2)
+        );
+    }
+
+    #[test]
+    fn answers_same_page_directly() {
+        let closure = closure(&[(1, 2), (2, 3), (3, 1), (5, 6)]);
+        assert_eq!(
+            Precedence::Same {
+                page: 1,
+                cycle: Some(vec![1, 2, 3, 1])
+            },
+            closure.precedence(1, 1)
+        );
+        assert_eq!(
+            "1 is the same page, and has to be printed before itself: 1|2, 2|3, 3|1",
+            closure.precedence(1, 1).to_string()
+        );
+        assert_eq!("5 is the same page", closure.precedence(5, 5).to_string());
+        assert_eq!("4 is the same page", closure.precedence(4, 4).to_string());
+    }
+}
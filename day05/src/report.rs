@@ -0,0 +1,240 @@
+use crate::{RuleIndex, Update};
+
+/// A rule `before|after` that an update breaks, with the positions the two
+/// pages were printed at.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Violation {
+    pub before: usize,
+    pub after: usize,
+    pub before_position: usize,
+    pub after_position: usize,
+}
+
+/// A page taken out of the update and put back at `to` in the corrected
+/// ordering.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PageMove {
+    pub page: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Debug)]
+pub struct UpdateReport {
+    /// Zero based index of the update in the input.
+    pub update: usize,
+    pub pages: Vec<usize>,
+    pub violations: Vec<Violation>,
+    /// The corrected ordering, or why there is none.
+    pub corrected: Result<Vec<usize>, String>,
+    pub moves: Vec<PageMove>,
+}
+
+impl Update {
+    fn violations(&self, index: &RuleIndex) -> Vec<Violation> {
+        let mut violations: Vec<Violation> = self
+            .applicable_rules(index)
+            .into_iter()
+            .filter(|(before, after)| before > after)
+            .map(|(before, after)| Violation {
+                before: self.values[before],
+                after: self.values[after],
+                before_position: before,
+                after_position: after,
+            })
+            .collect();
+        violations.sort_by_key(|v| (v.after_position, v.before_position));
+        violations
+    }
+}
+
+/// The fewest pages that have to be moved to turn `pages` into `corrected`:
+/// every page outside a longest run that is already in corrected order.
+fn page_moves(pages: &[usize], corrected: &[usize]) -> Vec<PageMove> {
+    let target: Vec<usize> = pages
+        .iter()
+        .map(|page| {
+            corrected
+                .iter()
+                .position(|p| p == page)
+                .expect("Page is kept")
+        })
+        .collect();
+    // lengths[i] is the longest increasing run of targets ending at i.
+    let mut lengths = vec![1; target.len()];
+    let mut previous: Vec<Option<usize>> = vec![None; target.len()];
+    for i in 0..target.len() {
+        for j in 0..i {
+            if target[j] < target[i] && lengths[j] + 1 > lengths[i] {
+                lengths[i] = lengths[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
+    }
+    let mut staying = vec![false; target.len()];
+    let mut current = (0..target.len()).max_by_key(|i| (lengths[*i], usize::MAX - i));
+    while let Some(i) = current {
+        staying[i] = true;
+        current = previous[i];
+    }
+    (0..target.len())
+        .filter(|i| !staying[*i])
+        .map(|i| PageMove {
+            page: pages[i],
+            from: i,
+            to: target[i],
+        })
+        .collect()
+}
+
+/// Reports on every update in `updates` that breaks at least one rule.
+pub fn build_reports(updates: &[Update], index: &RuleIndex) -> Vec<UpdateReport> {
+    updates
+        .iter()
+        .enumerate()
+        .filter(|(_, update)| !update.complies_with_rules(index))
+        .map(|(idx, update)| {
+            let mut corrected = update.clone();
+            let corrected = corrected
+                .correct(index)
+                .map(|c| c.values.clone())
+                .map_err(|error| error.to_string());
+            let moves = match &corrected {
+                Ok(corrected) => page_moves(&update.values, corrected),
+                Err(_) => Vec::new(),
+            };
+            UpdateReport {
+                update: idx,
+                pages: update.values.clone(),
+                violations: update.violations(index),
+                corrected,
+                moves,
+            }
+        })
+        .collect()
+}
+
+fn join(pages: &[usize]) -> String {
+    pages
+        .iter()
+        .map(|page| page.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub fn to_text(reports: &[UpdateReport]) -> String {
+    let mut out = String::new();
+    for report in reports {
+        out.push_str(&format!(
+            "Update {}: {}\n",
+            report.update,
+            join(&report.pages)
+        ));
+        for v in &report.violations {
+            out.push_str(&format!(
+                "  violates {}|{} ({} at {}, {} at {})\n",
+                v.before, v.after, v.before, v.before_position, v.after, v.after_position
+            ));
+        }
+        match &report.corrected {
+            Ok(corrected) => out.push_str(&format!("  corrected: {}\n", join(corrected))),
+            Err(error) => out.push_str(&format!("  cannot correct: {}\n", error)),
+        }
+        for m in &report.moves {
+            out.push_str(&format!("  move {} from {} to {}\n", m.page, m.from, m.to));
+        }
+    }
+    out
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub fn to_json(reports: &[UpdateReport]) -> String {
+    let reports: Vec<String> = reports
+        .iter()
+        .map(|report| {
+            let violations: Vec<String> = report
+                .violations
+                .iter()
+                .map(|v| {
+                    format!(
+                        "{{\"before\":{},\"after\":{},\"before_position\":{},\"after_position\":{}}}",
+                        v.before, v.after, v.before_position, v.after_position
+                    )
+                })
+                .collect();
+            let moves: Vec<String> = report
+                .moves
+                .iter()
+                .map(|m| format!("{{\"page\":{},\"from\":{},\"to\":{}}}", m.page, m.from, m.to))
+                .collect();
+            let corrected = match &report.corrected {
+                Ok(corrected) => format!("\"corrected\":[{}]", join(corrected)),
+                Err(error) => format!("\"corrected\":null,\"error\":{}", json_string(error)),
+            };
+            format!(
+                "{{\"update\":{},\"pages\":[{}],\"violations\":[{}],{},\"moves\":[{}]}}",
+                report.update,
+                join(&report.pages),
+                violations.join(","),
+                corrected,
+                moves.join(",")
+            )
+        })
+        .collect();
+    format!("[{}]\n", reports.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_only_out_of_place_pages() {
+        assert_eq!(
+            vec![
+                PageMove {
+                    page: 13,
+                    from: 1,
+                    to: 4
+                },
+                PageMove {
+                    page: 47,
+                    from: 4,
+                    to: 2
+                }
+            ],
+            page_moves(&[97, 13, 75, 29, 47], &[97, 75, 47, 29, 13])
+        );
+        assert_eq!(2, page_moves(&[3, 2, 1], &[1, 2, 3]).len());
+    }
+
+    #[test]
+    fn escapes_errors_in_json() {
+        let report = UpdateReport {
+            update: 0,
+            pages: vec![1, 2],
+            violations: Vec::new(),
+            corrected: Err("rule \"1|2\" \\ cycle\n".to_string()),
+            moves: Vec::new(),
+        };
+        assert_eq!(
+            "[{\"update\":0,\"pages\":[1,2],\"violations\":[],\"corrected\":null,\
+             \"error\":\"rule \\\"1|2\\\" \\\\ cycle\\n\",\"moves\":[]}]\n",
+            to_json(&[report])
+        );
+    }
+}
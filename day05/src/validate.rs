@@ -0,0 +1,246 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt::{Display, Formatter, Result},
+};
+
+use crate::{Rule, Update};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The input can still be solved, but the answer may not mean much.
+    Warning,
+    /// The line was skipped, so the answer would be wrong.
+    Error,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    /// One based line in the input, if the issue belongs to a single line.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self.severity {
+            Severity::Warning => write!(f, "warning: ")?,
+            Severity::Error => write!(f, "error: ")?,
+        }
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+#[derive(Debug)]
+pub struct Input {
+    pub rules: Vec<Rule>,
+    pub updates: Vec<Update>,
+    pub issues: Vec<Issue>,
+}
+
+impl Input {
+    pub fn has_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == Severity::Error)
+    }
+}
+
+fn parse_rule(line: &str) -> Option<Rule> {
+    let (before, after) = line.split_once('|')?;
+    Some(Rule {
+        before: before.trim().parse().ok()?,
+        after: after.trim().parse().ok()?,
+    })
+}
+
+fn parse_update(line: &str) -> Option<Update> {
+    let values: Option<Vec<usize>> = line
+        .split(',')
+        .map(|number| number.trim().parse().ok())
+        .collect();
+    Some(Update { values: values? })
+}
+
+/// Parses the rules and updates, skipping lines that can not be used and
+/// collecting everything that looks wrong along the way.
+pub fn parse_input(content: &str) -> Input {
+    let mut input = Input {
+        rules: Vec::new(),
+        updates: Vec::new(),
+        issues: Vec::new(),
+    };
+    let mut issue = |severity, line, message: String| {
+        input.issues.push(Issue {
+            severity,
+            line,
+            message,
+        })
+    };
+    let mut seen_rules: HashSet<(usize, usize)> = HashSet::new();
+    let mut in_updates = false;
+    // A single newline at the end of the file does not start an empty line.
+    let content = content.strip_suffix('\n').unwrap_or(content);
+    for (idx, line) in content.split('\n').enumerate() {
+        let line_number = Some(idx + 1);
+        let line = line.trim();
+        if line.is_empty() {
+            if in_updates {
+                issue(
+                    Severity::Warning,
+                    line_number,
+                    "empty line skipped".to_string(),
+                );
+            }
+            in_updates = true;
+            continue;
+        }
+        if !in_updates {
+            let Some(rule) = parse_rule(line) else {
+                issue(
+                    Severity::Error,
+                    line_number,
+                    format!("rule {:?} is not of the form before|after", line),
+                );
+                continue;
+            };
+            if rule.before == rule.after {
+                issue(
+                    Severity::Error,
+                    line_number,
+                    format!("rule {}|{} can never be satisfied", rule.before, rule.after),
+                );
+                continue;
+            }
+            if !seen_rules.insert((rule.before, rule.after)) {
+                issue(
+                    Severity::Warning,
+                    line_number,
+                    format!("rule {}|{} is repeated", rule.before, rule.after),
+                );
+                continue;
+            }
+            if seen_rules.contains(&(rule.after, rule.before)) {
+                issue(
+                    Severity::Warning,
+                    line_number,
+                    format!(
+                        "rule {}|{} contradicts rule {}|{}",
+                        rule.before, rule.after, rule.after, rule.before
+                    ),
+                );
+            }
+            input.rules.push(rule);
+            continue;
+        }
+        let Some(update) = parse_update(line) else {
+            issue(
+                Severity::Error,
+                line_number,
+                format!("update {:?} is not a list of page numbers", line),
+            );
+            continue;
+        };
+        let mut printed = HashSet::new();
+        let duplicates: BTreeSet<usize> = update
+            .values
+            .iter()
+            .filter(|page| !printed.insert(**page))
+            .copied()
+            .collect();
+        if !duplicates.is_empty() {
+            issue(
+                Severity::Error,
+                line_number,
+                format!("update prints pages {:?} more than once", duplicates),
+            );
+            continue;
+        }
+        if update.values.len() % 2 == 0 {
+            issue(
+                Severity::Warning,
+                line_number,
+                format!(
+                    "update has {} pages, so it has no single middle page",
+                    update.values.len()
+                ),
+            );
+        }
+        input.updates.push(update);
+    }
+    if !in_updates {
+        issue(
+            Severity::Error,
+            None,
+            "no updates after the rules".to_string(),
+        );
+    }
+    let printed: HashSet<usize> = input
+        .updates
+        .iter()
+        .flat_map(|update| update.values.iter().copied())
+        .collect();
+    let never_printed: BTreeSet<usize> = input
+        .rules
+        .iter()
+        .flat_map(|rule| [rule.before, rule.after])
+        .filter(|page| !printed.contains(page))
+        .collect();
+    if !never_printed.is_empty() {
+        issue(
+            Severity::Warning,
+            None,
+            format!(
+                "pages {:?} appear in rules but are never printed",
+                never_printed
+            ),
+        );
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_trailing_newline() {
+        let input = parse_input("1|2\n2|3\n\n1,2,3\n3,2,1\n");
+        assert_eq!(2, input.rules.len());
+        assert_eq!(2, input.updates.len());
+        assert!(input.issues.is_empty());
+    }
+
+    #[test]
+    fn reports_problems() {
+        let input = parse_input("1|2\n1|2\n2|1\n4|4\n5-6\n\n1,2\n1,2,1\n\n2,x,1");
+        let messages: Vec<String> = input.issues.iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            vec![
+                "warning: line 2: rule 1|2 is repeated",
+                "warning: line 3: rule 2|1 contradicts rule 1|2",
+                "error: line 4: rule 4|4 can never be satisfied",
+                "error: line 5: rule \"5-6\" is not of the form before|after",
+                "warning: line 7: update has 2 pages, so it has no single middle page",
+                "error: line 8: update prints pages {1} more than once",
+                "warning: line 9: empty line skipped",
+                "error: line 10: update \"2,x,1\" is not a list of page numbers",
+            ],
+            messages
+        );
+        assert!(input.has_errors());
+        assert_eq!(1, input.updates.len());
+    }
+
+    #[test]
+    fn reports_pages_never_printed() {
+        let input = parse_input("1|2\n3|4\n\n1,2,5");
+        assert_eq!(
+            "warning: pages {3, 4} appear in rules but are never printed",
+            input.issues[0].to_string()
+        );
+    }
+}
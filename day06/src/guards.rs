@@ -0,0 +1,267 @@
+use std::collections::BTreeSet;
+
+use crate::{Game, Guard, Outcome};
+
+/// Where a guard is after every move of its patrol, counting turns as moves,
+/// so that guards patrolling at the same time can be compared tick by tick.
+struct Trajectory {
+    positions: Vec<(usize, usize)>,
+    /// First tick of the cycle and its length in ticks, for a guard that is
+    /// stuck in a loop.
+    cycle: Option<(usize, usize)>,
+}
+
+impl Trajectory {
+    fn new(game: &Game) -> (Trajectory, Outcome) {
+        let start = game.current_position;
+        let phase = game.rule.phase(game.turns_taken);
+        let mut states = vec![(start.x, start.y, game.current_direction, phase)];
+        let outcome = game.clone().run_with(|state| {
+            states.push((
+                state.current_position.x,
+                state.current_position.y,
+                state.current_direction,
+                state.rule.phase(state.turns_taken),
+            ))
+        });
+        let cycle = match outcome {
+            Outcome::Exited { .. } => None,
+            Outcome::Looped { .. } => {
+                let last = states.len() - 1;
+                let first = states[..last]
+                    .iter()
+                    .position(|state| *state == states[last])
+                    .expect("Loop state was seen before");
+                Some((first, last - first))
+            }
+        };
+        let positions = states.iter().map(|(x, y, _, _)| (*x, *y)).collect();
+        (Trajectory { positions, cycle }, outcome)
+    }
+
+    /// The guard's cell at `tick`, or `None` once it has left the map.
+    fn at(&self, tick: usize) -> Option<(usize, usize)> {
+        if tick < self.positions.len() {
+            return Some(self.positions[tick]);
+        }
+        let (first, length) = self.cycle?;
+        Some(self.positions[first + (tick - first) % length])
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+/// First tick at which two guards stand on the same cell. Once both guards
+/// are in their loops the pair repeats with the least common multiple of the
+/// loop lengths, so nothing new can happen after that.
+fn first_meeting(a: &Trajectory, b: &Trajectory) -> Option<(usize, (usize, usize))> {
+    let horizon = match (a.cycle, b.cycle) {
+        (None, None) => a.positions.len().max(b.positions.len()),
+        (None, Some(_)) => a.positions.len(),
+        (Some(_), None) => b.positions.len(),
+        (Some((_, a_length)), Some((_, b_length))) => {
+            a.positions.len().max(b.positions.len()) + a_length / gcd(a_length, b_length) * b_length
+        }
+    };
+    (0..horizon).find_map(|tick| match (a.at(tick), b.at(tick)) {
+        (Some(a_cell), Some(b_cell)) if a_cell == b_cell => Some((tick, a_cell)),
+        _ => None,
+    })
+}
+
+pub struct GuardReport {
+    pub guard: Guard,
+    pub outcome: Outcome,
+    pub visited: BTreeSet<(usize, usize)>,
+}
+
+/// Two guards, by index, standing on `cell` at the same tick.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Meeting {
+    pub guards: (usize, usize),
+    pub tick: usize,
+    pub cell: (usize, usize),
+}
+
+pub struct Patrols {
+    pub guards: Vec<GuardReport>,
+    pub union: BTreeSet<(usize, usize)>,
+    /// The first meeting of every pair of guards that ever meets.
+    pub meetings: Vec<Meeting>,
+}
+
+/// Lets every guard on the map patrol at the same time, each taking one step
+/// or turn per tick. Guards do not block each other.
+pub fn patrol_all(game: &Game) -> Patrols {
+    let mut guards = Vec::new();
+    let mut trajectories = Vec::new();
+    for guard in &game.guards {
+        let (trajectory, outcome) = Trajectory::new(&game.for_guard(guard));
+        guards.push(GuardReport {
+            guard: *guard,
+            outcome,
+            visited: trajectory.positions.iter().copied().collect(),
+        });
+        trajectories.push(trajectory);
+    }
+    let union = guards
+        .iter()
+        .flat_map(|report| report.visited.iter().copied())
+        .collect();
+    let mut meetings = Vec::new();
+    for a in 0..trajectories.len() {
+        for b in a + 1..trajectories.len() {
+            if let Some((tick, cell)) = first_meeting(&trajectories[a], &trajectories[b]) {
+                meetings.push(Meeting {
+                    guards: (a, b),
+                    tick,
+                    cell,
+                });
+            }
+        }
+    }
+    Patrols {
+        guards,
+        union,
+        meetings,
+    }
+}
+
+fn cells(cells: &BTreeSet<(usize, usize)>) -> String {
+    cells
+        .iter()
+        .map(|(x, y)| format!("[{},{}]", x, y))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub fn to_text(patrols: &Patrols) -> String {
+    let mut out = String::new();
+    for (idx, report) in patrols.guards.iter().enumerate() {
+        out.push_str(&format!(
+            "Guard {} from {},{} heading {}: {} cells, {}\n",
+            idx,
+            report.guard.x,
+            report.guard.y,
+            report.guard.direction.glyph(),
+            report.visited.len(),
+            report.outcome
+        ));
+    }
+    out.push_str(&format!("All guards: {} cells\n", patrols.union.len()));
+    if patrols.meetings.is_empty() {
+        out.push_str("No guards meet\n");
+    }
+    for meeting in &patrols.meetings {
+        out.push_str(&format!(
+            "Guards {} and {} meet at {},{} after {} moves\n",
+            meeting.guards.0, meeting.guards.1, meeting.cell.0, meeting.cell.1, meeting.tick
+        ));
+    }
+    out
+}
+
+pub fn to_json(patrols: &Patrols) -> String {
+    let guards: Vec<String> = patrols
+        .guards
+        .iter()
+        .map(|report| {
+            let outcome = match report.outcome {
+                Outcome::Exited { x, y } => format!("\"exited\":[{},{}]", x, y),
+                Outcome::Looped {
+                    x,
+                    y,
+                    direction,
+                    length,
+                } => format!(
+                    "\"looped\":{{\"cell\":[{},{}],\"heading\":\"{}\",\"length\":{}}}",
+                    x,
+                    y,
+                    direction.glyph(),
+                    length
+                ),
+            };
+            format!(
+                "{{\"start\":[{},{}],\"heading\":\"{}\",{},\"visited\":[{}]}}",
+                report.guard.x,
+                report.guard.y,
+                report.guard.direction.glyph(),
+                outcome,
+                cells(&report.visited)
+            )
+        })
+        .collect();
+    let meetings: Vec<String> = patrols
+        .meetings
+        .iter()
+        .map(|m| {
+            format!(
+                "{{\"guards\":[{},{}],\"tick\":{},\"cell\":[{},{}]}}",
+                m.guards.0, m.guards.1, m.tick, m.cell.0, m.cell.1
+            )
+        })
+        .collect();
+    format!(
+        "{{\"guards\":[{}],\"union\":[{}],\"meetings\":[{}]}}\n",
+        guards.join(","),
+        cells(&patrols.union),
+        meetings.join(",")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_game, parse_game};
+
+    #[test]
+    fn guards_walking_towards_each_other_meet() {
+        let game = parse_game(".....\n.>.<.\n.....\n.....\n.....");
+        let patrols = patrol_all(&game);
+        assert_eq!(4, patrols.guards[0].visited.len());
+        assert_eq!(4, patrols.guards[1].visited.len());
+        assert_eq!(5, patrols.union.len());
+        assert_eq!(
+            vec![Meeting {
+                guards: (0, 1),
+                tick: 1,
+                cell: (2, 1)
+            }],
+            patrols.meetings
+        );
+    }
+
+    #[test]
+    fn looping_guards_are_compared_over_their_cycles() {
+        // The last two guards circle the same loop half a lap apart, the
+        // first one walks into the loop right behind the second.
+        let game = parse_game(".#...\n>...#\n.^.v.\n#....\n...#.");
+        let patrols = patrol_all(&game);
+        assert!(patrols
+            .guards
+            .iter()
+            .all(|report| matches!(report.outcome, Outcome::Looped { .. })));
+        assert_eq!(8, patrols.guards[1].visited.len());
+        assert_eq!(9, patrols.union.len());
+        assert_eq!(
+            vec![Meeting {
+                guards: (0, 1),
+                tick: 1,
+                cell: (1, 1)
+            }],
+            patrols.meetings
+        );
+    }
+
+    #[test]
+    fn single_guard_matches_part_one() {
+        let patrols = patrol_all(&build_game("example"));
+        assert_eq!(41, patrols.union.len());
+        assert!(patrols.meetings.is_empty());
+    }
+}
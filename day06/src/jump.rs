@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use crate::{get_index, turning::TurnRule, Direction, Game, Outcome, PositionType};
+
+/// For every cell and heading, the row or column of the nearest obstacle the
+/// guard would run into, so a whole straight stretch of the patrol can be
+/// taken in one jump.
+#[derive(Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct JumpTable {
+    max_x: usize,
+    max_y: usize,
+    blocked: Vec<bool>,
+    /// Indexed by cell index * 4 + `Direction::index`. `None` when the guard
+    /// would walk off the map instead.
+    next_obstacle: Vec<Option<usize>>,
+}
+
+/// Table entries overwritten by `add_obstruction`, to put back with `undo`.
+pub struct Undo {
+    cell: usize,
+    /// Whether the cell was open before, so the obstruction has to go again.
+    placed: bool,
+    entries: Vec<(usize, Option<usize>)>,
+}
+
+impl JumpTable {
+    pub fn new(game: &Game) -> JumpTable {
+        let blocked: Vec<bool> = game
+            .map
+            .iter()
+            .map(|pos| matches!(pos.pos_type, PositionType::BLOCKED))
+            .collect();
+        let mut table = JumpTable {
+            max_x: game.max_x,
+            max_y: game.max_y,
+            blocked,
+            next_obstacle: vec![None; game.map.len() * 4],
+        };
+        for x in 0..=table.max_x {
+            let mut last = None;
+            for y in 0..=table.max_y {
+                table.set(x, y, Direction::UP, last);
+                if table.is_blocked(x, y) {
+                    last = Some(y);
+                }
+            }
+            last = None;
+            for y in (0..=table.max_y).rev() {
+                table.set(x, y, Direction::DOWN, last);
+                if table.is_blocked(x, y) {
+                    last = Some(y);
+                }
+            }
+        }
+        for y in 0..=table.max_y {
+            let mut last = None;
+            for x in 0..=table.max_x {
+                table.set(x, y, Direction::LEFT, last);
+                if table.is_blocked(x, y) {
+                    last = Some(x);
+                }
+            }
+            last = None;
+            for x in (0..=table.max_x).rev() {
+                table.set(x, y, Direction::RIGHT, last);
+                if table.is_blocked(x, y) {
+                    last = Some(x);
+                }
+            }
+        }
+        table
+    }
+
+    fn entry(&self, x: usize, y: usize, direction: Direction) -> usize {
+        get_index(x, y, self.max_y) * 4 + direction.index()
+    }
+
+    fn set(&mut self, x: usize, y: usize, direction: Direction, obstacle: Option<usize>) {
+        let entry = self.entry(x, y, direction);
+        self.next_obstacle[entry] = obstacle;
+    }
+
+    fn is_blocked(&self, x: usize, y: usize) -> bool {
+        self.blocked[get_index(x, y, self.max_y)]
+    }
+
+    /// Places an obstruction at `x`, `y`. Only the cells that can see it in a
+    /// straight line up to the next obstacle change, so this takes time
+    /// proportional to the width of the map rather than its area.
+    pub fn add_obstruction(&mut self, x: usize, y: usize) -> Undo {
+        let mut undo = Undo {
+            cell: get_index(x, y, self.max_y),
+            placed: false,
+            entries: Vec::new(),
+        };
+        if self.blocked[undo.cell] {
+            return undo;
+        }
+        undo.placed = true;
+        self.blocked[undo.cell] = true;
+        let mut update = |table: &mut JumpTable, cx: usize, cy: usize, direction, value| {
+            let entry = table.entry(cx, cy, direction);
+            undo.entries.push((entry, table.next_obstacle[entry]));
+            table.next_obstacle[entry] = Some(value);
+            table.is_blocked(cx, cy)
+        };
+        for cy in y + 1..=self.max_y {
+            if update(self, x, cy, Direction::UP, y) {
+                break;
+            }
+        }
+        for cy in (0..y).rev() {
+            if update(self, x, cy, Direction::DOWN, y) {
+                break;
+            }
+        }
+        for cx in x + 1..=self.max_x {
+            if update(self, cx, y, Direction::LEFT, x) {
+                break;
+            }
+        }
+        for cx in (0..x).rev() {
+            if update(self, cx, y, Direction::RIGHT, x) {
+                break;
+            }
+        }
+        undo
+    }
+
+    /// Takes back an obstruction placed by `add_obstruction`.
+    pub fn undo(&mut self, undo: Undo) {
+        for (entry, value) in undo.entries.into_iter().rev() {
+            self.next_obstacle[entry] = value;
+        }
+        if undo.placed {
+            self.blocked[undo.cell] = false;
+        }
+    }
+
+    /// Walks the guard from `x`, `y` one straight stretch at a time, calling
+    /// `on_stretch` with the first and last cell of each stretch. Loops are
+    /// detected the same way as `Game::run`, from the states right after a
+    /// turn, so both report the same outcome.
+    pub fn walk<F>(
+        &self,
+        x: usize,
+        y: usize,
+        direction: Direction,
+        rule: TurnRule,
+        mut on_stretch: F,
+    ) -> Outcome
+    where
+        F: FnMut((usize, usize), (usize, usize)),
+    {
+        let mut turned_at: HashMap<(usize, usize, Direction, usize), usize> = HashMap::new();
+        let (mut x, mut y, mut direction, mut steps, mut turns) = (x, y, direction, 0, 0);
+        loop {
+            let obstacle = self.next_obstacle[self.entry(x, y, direction)];
+            let (to_x, to_y) = match (direction, obstacle) {
+                (Direction::UP, Some(o)) => (x, o + 1),
+                (Direction::UP, None) => (x, 0),
+                (Direction::DOWN, Some(o)) => (x, o - 1),
+                (Direction::DOWN, None) => (x, self.max_y),
+                (Direction::LEFT, Some(o)) => (o + 1, y),
+                (Direction::LEFT, None) => (0, y),
+                (Direction::RIGHT, Some(o)) => (o - 1, y),
+                (Direction::RIGHT, None) => (self.max_x, y),
+            };
+            on_stretch((x, y), (to_x, to_y));
+            steps += x.abs_diff(to_x) + y.abs_diff(to_y);
+            (x, y) = (to_x, to_y);
+            if obstacle.is_none() {
+                return Outcome::Exited { x, y };
+            }
+            direction = rule.turn(get_index(x, y, self.max_y), direction, turns);
+            turns += 1;
+            if let Some(first) = turned_at.insert((x, y, direction, rule.phase(turns)), steps) {
+                return Outcome::Looped {
+                    x,
+                    y,
+                    direction,
+                    length: steps - first,
+                };
+            }
+        }
+    }
+}
@@ -1,12 +1,32 @@
-use std::{fs::File, io::Read};
+mod guards;
+mod jump;
+mod obstructions;
+mod replay;
+mod turning;
 
+use std::{
+    collections::HashMap,
+    env,
+    fmt::{Display, Formatter, Result},
+    fs::File,
+    io::Read,
+    thread,
+    time::Duration,
+};
+
+use jump::JumpTable;
+use obstructions::Obstruction;
+use turning::TurnRule;
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Copy, Debug)]
 enum PositionType {
     OPEN,
     BLOCKED,
 }
 
-#[derive(Debug)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 enum Direction {
     UP,
     DOWN,
@@ -14,6 +34,88 @@ enum Direction {
     RIGHT,
 }
 
+impl Direction {
+    fn index(&self) -> usize {
+        match self {
+            Direction::UP => 0,
+            Direction::DOWN => 1,
+            Direction::LEFT => 2,
+            Direction::RIGHT => 3,
+        }
+    }
+
+    fn turned_right(&self) -> Direction {
+        match self {
+            Direction::UP => Direction::RIGHT,
+            Direction::DOWN => Direction::LEFT,
+            Direction::LEFT => Direction::UP,
+            Direction::RIGHT => Direction::DOWN,
+        }
+    }
+
+    fn turned_left(&self) -> Direction {
+        match self {
+            Direction::UP => Direction::LEFT,
+            Direction::DOWN => Direction::RIGHT,
+            Direction::LEFT => Direction::DOWN,
+            Direction::RIGHT => Direction::UP,
+        }
+    }
+
+    fn from_glyph(c: char) -> Option<Direction> {
+        match c {
+            '^' => Some(Direction::UP),
+            'v' => Some(Direction::DOWN),
+            '<' => Some(Direction::LEFT),
+            '>' => Some(Direction::RIGHT),
+            _ => None,
+        }
+    }
+
+    fn glyph(&self) -> char {
+        match self {
+            Direction::UP => '^',
+            Direction::DOWN => 'v',
+            Direction::LEFT => '<',
+            Direction::RIGHT => '>',
+        }
+    }
+}
+
+/// How a patrol ends: the guard either walks off the map or comes back to a
+/// position and heading it has been in before.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    /// The guard left the map from this cell.
+    Exited { x: usize, y: usize },
+    /// The guard is stuck in a loop. The cycle starts when the guard is at
+    /// `x`, `y` heading `direction` and takes `length` steps to come back.
+    Looped {
+        x: usize,
+        y: usize,
+        direction: Direction,
+        length: usize,
+    },
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Outcome::Exited { x, y } => write!(f, "exited at {},{}", x, y),
+            Outcome::Looped {
+                x,
+                y,
+                direction,
+                length,
+            } => write!(
+                f,
+                "looped from {},{} heading {:?} every {} steps",
+                x, y, direction, length
+            ),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 struct Position {
     x: usize,
@@ -21,6 +123,18 @@ struct Position {
     visited: bool,
     pos_type: PositionType,
 }
+
+/// Where a guard stands on the map before the patrol starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Guard {
+    x: usize,
+    y: usize,
+    direction: Direction,
+}
+
+/// The map and the patrol of a single guard, the first one in reading order
+/// unless another one is picked with `for_guard`.
+#[derive(Clone)]
 struct Game {
     map: Vec<Position>,
     max_x: usize,
@@ -28,13 +142,30 @@ struct Game {
     current_position: Position,
     current_direction: Direction,
     steps_taken: usize,
+    /// Every guard on the map, in reading order.
+    guards: Vec<Guard>,
+    rule: TurnRule,
+    turns_taken: usize,
 }
 
 fn get_index(x: usize, y: usize, max_y: usize) -> usize {
-    return (max_y + 1) * y + x;
+    (max_y + 1) * y + x
 }
 
 impl Game {
+    /// The game as it starts for `guard`, with only its start visited.
+    fn for_guard(&self, guard: &Guard) -> Game {
+        let mut game = self.clone();
+        game.map.iter_mut().for_each(|pos| pos.visited = false);
+        let start = get_index(guard.x, guard.y, game.max_y);
+        game.map[start].visited = true;
+        game.current_position = game.map[start];
+        game.current_direction = guard.direction;
+        game.steps_taken = 0;
+        game.turns_taken = 0;
+        game
+    }
+
     fn add_block(&mut self, x: usize, y: usize) {
         self.map
             .get_mut(get_index(x, y, self.max_y))
@@ -43,10 +174,10 @@ impl Game {
     }
 
     fn get_map_position(&self, x: usize, y: usize) -> Position {
-        self.map
+        *self
+            .map
             .get(get_index(x, y, self.max_y))
             .expect("Position should exists")
-            .clone()
     }
 
     fn mark_visited(&mut self, x: usize, y: usize) {
@@ -90,44 +221,76 @@ impl Game {
         }
     }
 
-    fn turn_right(&mut self) {
-        self.current_direction = match self.current_direction {
-            Direction::UP => Direction::RIGHT,
-            Direction::DOWN => Direction::LEFT,
-            Direction::LEFT => Direction::UP,
-            Direction::RIGHT => Direction::DOWN,
-        }
+    fn turn(&mut self) {
+        let cell = get_index(self.current_position.x, self.current_position.y, self.max_y);
+        self.current_direction = self
+            .rule
+            .turn(cell, self.current_direction, self.turns_taken);
+        self.turns_taken += 1;
     }
 
     fn perform_move(&mut self) -> bool {
-        // println!("Move is in bounds: {}", self.is_move_in_bounds());
         if !self.is_move_in_bounds() {
             return false;
         }
         if self.can_move() {
             // Move
-            let cur_pos = self.current_position.clone();
+            let cur_pos = self.current_position;
             let new_pos = match self.current_direction {
                 Direction::UP => self.get_map_position(cur_pos.x, cur_pos.y - 1),
                 Direction::DOWN => self.get_map_position(cur_pos.x, cur_pos.y + 1),
                 Direction::LEFT => self.get_map_position(cur_pos.x - 1, cur_pos.y),
                 Direction::RIGHT => self.get_map_position(cur_pos.x + 1, cur_pos.y),
             };
-            self.current_position = new_pos.clone();
+            self.current_position = new_pos;
             self.mark_visited(new_pos.x, new_pos.y);
-            // println!(
-            //     "Moving from {},{} to {},{} due to direction {:?}",
-            //     cur_pos.x, cur_pos.y, new_pos.x, new_pos.y, self.current_direction
-            // );
             return true;
         }
-        // println!("Turning right");
-        self.turn_right();
+        self.turn();
         true
     }
 
-    fn is_in_infinite_loop(&self) -> bool {
-        self.steps_taken > (self.max_x + 1) * (self.max_y + 1)
+    /// Walks the guard until it leaves the map or repeats a state. A loop has
+    /// to turn somewhere, so only the position and new heading after each
+    /// turn are recorded, with the number of steps taken when first reached.
+    /// A repeat is detected at the first turn that happens twice.
+    fn run(&mut self) -> Outcome {
+        self.run_with(|_| {})
+    }
+
+    /// Same as `run`, calling `on_move` after every step or turn.
+    fn run_with<F>(&mut self, mut on_move: F) -> Outcome
+    where
+        F: FnMut(&Game),
+    {
+        let mut turned_at: HashMap<(usize, usize, Direction, usize), usize> = HashMap::new();
+        loop {
+            let turns = self.turns_taken;
+            if !self.perform_move() {
+                return Outcome::Exited {
+                    x: self.current_position.x,
+                    y: self.current_position.y,
+                };
+            }
+            on_move(self);
+            if self.turns_taken == turns {
+                continue;
+            }
+            let state = (
+                self.current_position.x,
+                self.current_position.y,
+                self.current_direction,
+                self.rule.phase(self.turns_taken),
+            );
+            if let Some(steps) = turned_at.insert(state, self.steps_taken) {
+                return Outcome::Looped {
+                    x: state.0,
+                    y: state.1,
+                    direction: state.2,
+                    length: self.steps_taken - steps,
+                };
+            }
+        }
     }
 }
 
@@ -143,72 +306,464 @@ fn build_game(file_name: &str) -> Game {
     input_file
         .read_to_string(&mut content)
         .expect("Can read file");
+    parse_game(&content)
+}
 
-    let line_length = content.clone().lines().next().expect("Has line").len();
+fn parse_game(content: &str) -> Game {
+    let line_length = content.lines().next().expect("Has line").len();
     let mut map: Vec<Position> = Vec::new();
-    let mut current_pos: Option<Position> = None;
+    let mut guards: Vec<Guard> = Vec::new();
 
     content.split("\n").enumerate().for_each(|(y_idx, line)| {
         line.chars().enumerate().for_each(|(x_idx, c)| {
             let pos = Position {
                 x: x_idx,
                 y: y_idx,
-                visited: c == '^',
+                visited: false,
                 pos_type: match c {
-                    '^' | '.' => PositionType::OPEN,
                     '#' => PositionType::BLOCKED,
-                    _ => unreachable!(),
+                    '.' | '^' | 'v' | '<' | '>' => PositionType::OPEN,
+                    _ => panic!("Unexpected character {:?} in map", c),
                 },
             };
             map.push(pos);
-            if c == '^' {
-                current_pos = Some(pos.clone());
+            if let Some(direction) = Direction::from_glyph(c) {
+                guards.push(Guard {
+                    x: x_idx,
+                    y: y_idx,
+                    direction,
+                });
             }
         });
     });
 
-    Game {
+    let first = *guards.first().expect("Starting position exists");
+    let game = Game {
         map,
         max_x: line_length - 1,
         max_y: line_length - 1,
-        current_position: current_pos.expect("Starting position exists"),
-        current_direction: Direction::UP,
+        current_position: Position {
+            x: first.x,
+            y: first.y,
+            visited: false,
+            pos_type: PositionType::OPEN,
+        },
+        current_direction: first.direction,
         steps_taken: 0,
-    }
+        guards,
+        rule: TurnRule::Right,
+        turns_taken: 0,
+    };
+    game.for_guard(&first)
 }
 
-fn main() {
-    let file_name = "input";
+/// Cells where one extra obstruction traps the guard in a loop. An
+/// obstruction off the guard's original path can not change the patrol, so
+/// only cells on that path are tried, spread over all available cores. Each
+/// worker places the obstructions in its own copy of the jump table. When the
+/// guard already loops without help, every open cell off the path keeps it
+/// looping and counts as well, whatever the turning rule.
+fn find_loop_obstructions(game: &Game) -> Vec<Obstruction> {
+    let table = JumpTable::new(game);
+    let start = game.current_position;
+    let mut patrol = game.clone();
+    let original_loop = match patrol.run() {
+        Outcome::Looped { length, .. } => Some(length),
+        Outcome::Exited { .. } => None,
+    };
+    let off_path = patrol
+        .map
+        .iter()
+        .filter(|pos| matches!(pos.pos_type, PositionType::OPEN) && !pos.visited)
+        .filter_map(|pos| {
+            original_loop.map(|length| Obstruction {
+                x: pos.x,
+                y: pos.y,
+                length,
+            })
+        });
+    let candidates: Vec<(usize, usize)> = patrol
+        .map
+        .iter()
+        .filter(|pos| pos.visited)
+        .map(|pos| (pos.x, pos.y))
+        .collect();
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = candidates.len().div_ceil(threads).max(1);
+    let mut found: Vec<Obstruction> = thread::scope(|scope| {
+        let workers: Vec<_> = candidates
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let mut table = table.clone();
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .filter_map(|(x, y)| {
+                            let undo = table.add_obstruction(*x, *y);
+                            let outcome = table.walk(
+                                start.x,
+                                start.y,
+                                game.current_direction,
+                                game.rule,
+                                |_, _| {},
+                            );
+                            table.undo(undo);
+                            match outcome {
+                                Outcome::Looped { length, .. } => Some(Obstruction {
+                                    x: *x,
+                                    y: *y,
+                                    length,
+                                }),
+                                Outcome::Exited { .. } => None,
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("Worker finished"))
+            .chain(off_path)
+            .collect()
+    });
+    found.sort_by_key(|o| (o.x, o.y));
+    found
+}
+
+enum Command {
+    Solve,
+    Replay,
+    Guards,
+    Obstructions,
+}
 
-    let mut game = build_game(&file_name);
-    loop {
-        let has_moved = game.perform_move();
+struct Options {
+    file_name: String,
+    command: Command,
+    /// Extra obstruction placed before the patrol is replayed.
+    block: Option<(usize, usize)>,
+    delay: Duration,
+    /// Directory to write the frames to instead of animating them.
+    frames: Option<String>,
+    /// Only every n-th move is drawn.
+    every: usize,
+    json: bool,
+    rule: TurnRule,
+    /// List the loop length next to every obstruction.
+    lengths: bool,
+    /// Draw the obstructions onto the map instead of listing them.
+    overlay: bool,
+}
+
+/// Reads `[replay | guards | obstructions] [--turn RULE] [--block X,Y]
+/// [--delay MS] [--frames DIR] [--every N] [--json] [--lengths] [--map]
+/// [--input FILE]`. Without a command both parts are solved for "input",
+/// following the first guard on the map. RULE is one of `right` (the
+/// default), `left`, `around`, `alternate` or `random:SEED`. `replay`
+/// animates the patrol in the terminal, or writes one text file per frame
+/// into DIR when `--frames` is given. `--block`, `--delay` and `--every` only
+/// apply to `replay`, and `--block` is refused with any other command.
+/// `guards` lets all guards patrol at once and reports the cells each of them
+/// visits and where they meet. `obstructions` lists the cells counted for
+/// part two, with the length of the loop each one causes when `--lengths` is
+/// given, or draws them onto the map with `--map`, which can not be combined
+/// with `--json`.
+fn parse_options() -> Options {
+    let mut options = Options {
+        file_name: "input".to_string(),
+        command: Command::Solve,
+        block: None,
+        delay: Duration::from_millis(50),
+        frames: None,
+        every: 1,
+        json: false,
+        rule: TurnRule::Right,
+        lengths: false,
+        overlay: false,
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => options.file_name = args.next().expect("--input needs a file name"),
+            "replay" => options.command = Command::Replay,
+            "guards" => options.command = Command::Guards,
+            "obstructions" => options.command = Command::Obstructions,
+            "--lengths" => options.lengths = true,
+            "--map" => options.overlay = true,
+            "--json" => options.json = true,
+            "--turn" => {
+                options.rule = TurnRule::from_spec(&args.next().expect("--turn needs a rule"))
+            }
+            "--block" => {
+                let spec = args.next().expect("--block needs a cell");
+                let (x, y) = spec.split_once(',').expect("Cell is of the form X,Y");
+                options.block = Some((
+                    x.parse().expect("X is a number"),
+                    y.parse().expect("Y is a number"),
+                ));
+            }
+            "--delay" => {
+                options.delay = Duration::from_millis(
+                    args.next()
+                        .expect("--delay needs milliseconds")
+                        .parse()
+                        .expect("Delay is a number"),
+                )
+            }
+            "--frames" => options.frames = Some(args.next().expect("--frames needs a directory")),
+            "--every" => {
+                options.every = args
+                    .next()
+                    .expect("--every needs a number")
+                    .parse()
+                    .expect("Every is a number");
+                assert!(options.every > 0, "--every must be at least 1");
+            }
+            _ => panic!("Unknown argument {}", arg),
+        }
+    }
+    if options.block.is_some() && !matches!(options.command, Command::Replay) {
+        panic!("--block only works with replay");
+    }
+    if options.overlay && options.json {
+        panic!("--map can not be combined with --json");
+    }
+    options
+}
 
-        if !has_moved {
-            break;
+fn main() {
+    let options = parse_options();
+    let mut game = build_game(&options.file_name);
+    game.rule = options.rule;
+    if let Command::Guards = options.command {
+        let patrols = guards::patrol_all(&game);
+        match options.json {
+            true => print!("{}", guards::to_json(&patrols)),
+            false => print!("{}", guards::to_text(&patrols)),
         }
+        return;
     }
-    let visited_positions: Vec<_> = game.map.iter().filter(|val| val.visited).collect();
+    if let Command::Obstructions = options.command {
+        let found = find_loop_obstructions(&game);
+        match (options.overlay, options.json) {
+            (true, _) => {
+                let mut patrol = game.clone();
+                patrol.run();
+                print!("{}", obstructions::render_overlay(&game, &patrol, &found))
+            }
+            (false, true) => print!("{}", obstructions::to_json(&found, options.lengths)),
+            (false, false) => print!("{}", obstructions::to_text(&found, options.lengths)),
+        }
+        return;
+    }
+    if let Command::Replay = options.command {
+        if let Some((x, y)) = options.block {
+            assert!(
+                x <= game.max_x && y <= game.max_y,
+                "Obstruction is outside the map"
+            );
+            game.add_block(x, y);
+        }
+        let outcome = match &options.frames {
+            Some(dir) => replay::write_frames(&game, options.block, options.every, dir)
+                .expect("Can write frames"),
+            None => replay::animate(&game, options.block, options.every, options.delay),
+        };
+        println!("Guard {}", outcome);
+        return;
+    }
+
+    let mut patrol = game.clone();
+    patrol.run();
+    let visited_positions: Vec<_> = patrol.map.iter().filter(|val| val.visited).collect();
     println!("Part one: {}", visited_positions.len());
-    let mut part_two_count: usize = 0;
-    for x in 0..=game.max_x {
-        for y in 0..=game.max_y {
-            let mut updated_game = build_game(&file_name);
-
-            updated_game.add_block(x, y);
-            loop {
-                let has_moved = updated_game.perform_move();
-
-                if updated_game.is_in_infinite_loop() {
-                    part_two_count += 1;
-                    break;
+    println!("Part two: {}", find_loop_obstructions(&game).len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_exits() {
+        let mut game = build_game("example");
+        assert_eq!(Outcome::Exited { x: 7, y: 9 }, game.run());
+        assert_eq!(41, game.map.iter().filter(|pos| pos.visited).count());
+    }
+
+    #[test]
+    fn example_loop_has_start_and_length() {
+        let mut game = build_game("example");
+        game.add_block(3, 6);
+        assert_eq!(
+            Outcome::Looped {
+                x: 4,
+                y: 1,
+                direction: Direction::RIGHT,
+                length: 18,
+            },
+            game.run()
+        );
+    }
+
+    fn loops_trying_every_cell(game: &Game) -> Vec<(usize, usize)> {
+        let mut every_cell = Vec::new();
+        for x in 0..=game.max_x {
+            for y in 0..=game.max_y {
+                // Blocking a wall again changes nothing.
+                if matches!(game.get_map_position(x, y).pos_type, PositionType::BLOCKED) {
+                    continue;
+                }
+                let mut updated_game = game.clone();
+                updated_game.add_block(x, y);
+                if let Outcome::Looped { .. } = updated_game.run() {
+                    every_cell.push((x, y));
                 }
+            }
+        }
+        every_cell
+    }
+
+    fn loop_cells(game: &Game) -> Vec<(usize, usize)> {
+        find_loop_obstructions(game)
+            .iter()
+            .map(|o| (o.x, o.y))
+            .collect()
+    }
+
+    const RULES: [TurnRule; 5] = [
+        TurnRule::Right,
+        TurnRule::Left,
+        TurnRule::Around,
+        TurnRule::Alternating,
+        TurnRule::Random(7),
+    ];
+
+    #[test]
+    fn obstructions_match_trying_every_cell() {
+        let game = build_game("example");
+        let every_cell = loops_trying_every_cell(&game);
+        assert_eq!(6, every_cell.len());
+        assert_eq!(every_cell, loop_cells(&game));
+    }
+
+    #[test]
+    fn obstruction_on_the_start_traps_the_guard_coming_back() {
+        let game = parse_game(
+            "....#.......\n\
+             ...#.#.##.#.\n\
+             .##.........\n\
+             ...#..#....#\n\
+             ....####....\n\
+             ##..#.......\n\
+             ..^.#.......\n\
+             #.##..#.#...\n\
+             .......#....\n\
+             .#.#........\n\
+             ....#...#...\n\
+             ............",
+        );
+        let every_cell = loops_trying_every_cell(&game);
+        assert_eq!(3, every_cell.len());
+        assert!(every_cell.contains(&(2, 6)));
+        assert_eq!(every_cell, loop_cells(&game));
+    }
+
+    #[test]
+    fn obstructions_match_trying_every_cell_when_already_looping() {
+        let game = parse_game(".#...\n....#\n#^...\n...#.\n.....");
+        assert!(matches!(game.clone().run(), Outcome::Looped { .. }));
+        let every_cell = loops_trying_every_cell(&game);
+        // Every open cell off the loop, blocking the loop itself frees the
+        // guard.
+        assert_eq!(15, every_cell.len());
+        assert_eq!(every_cell, loop_cells(&game));
+    }
+
+    #[test]
+    fn obstructions_match_trying_every_cell_for_every_rule() {
+        for rule in RULES {
+            for mut game in [build_game("example"), random_game(13, 5, 99)] {
+                game.rule = rule;
+                assert_eq!(
+                    loops_trying_every_cell(&game),
+                    loop_cells(&game),
+                    "{:?}",
+                    rule
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn left_turns_mirror_right_turns() {
+        let mut right = build_game("example");
+        let content = std::fs::read_to_string("example").expect("Can read file");
+        let mirrored: Vec<String> = content
+            .lines()
+            .map(|line| line.chars().rev().collect())
+            .collect();
+        let mut left = parse_game(&mirrored.join("\n"));
+        left.rule = TurnRule::Left;
+        assert_eq!(Outcome::Exited { x: 7, y: 9 }, right.run());
+        assert_eq!(Outcome::Exited { x: 2, y: 9 }, left.run());
+        assert_eq!(right.steps_taken, left.steps_taken);
+    }
+
+    /// Square map with roughly one obstacle in `density` cells and the guard
+    /// in the middle.
+    fn random_game(size: usize, density: u64, seed: u64) -> Game {
+        let mut state = seed;
+        let mut rows = Vec::new();
+        for y in 0..size {
+            let row: String = (0..size)
+                .map(|x| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    match (
+                        x == size / 2 && y == size / 2,
+                        state.is_multiple_of(density),
+                    ) {
+                        (true, _) => '^',
+                        (false, true) => '#',
+                        (false, false) => '.',
+                    }
+                })
+                .collect();
+            rows.push(row);
+        }
+        parse_game(&rows.join("\n"))
+    }
 
-                if !has_moved {
-                    break;
+    #[test]
+    fn jumps_agree_with_single_steps() {
+        for seed in 1..40 {
+            let mut game = random_game(17, 6, seed);
+            game.rule = RULES[seed as usize % RULES.len()];
+            let mut table = JumpTable::new(&game);
+            let start = game.current_position;
+            for (x, y) in [(3, 3), (8, 2), (12, 9), (8, 7), (0, 16)] {
+                let mut stepped = game.clone();
+                stepped.add_block(x, y);
+                let undo = table.add_obstruction(x, y);
+                let mut jumped_cells = 0;
+                let jumped = table.walk(
+                    start.x,
+                    start.y,
+                    game.current_direction,
+                    game.rule,
+                    |from, to| jumped_cells += from.0.abs_diff(to.0) + from.1.abs_diff(to.1),
+                );
+                let stepped_outcome = stepped.run();
+                assert_eq!(stepped_outcome, jumped, "seed {} block {},{}", seed, x, y);
+                if let Outcome::Exited { .. } = jumped {
+                    assert_eq!(stepped.steps_taken, jumped_cells);
                 }
+                table.undo(undo);
             }
+            assert!(table == JumpTable::new(&game));
         }
     }
-    println!("Part two: {}", part_two_count);
 }
@@ -0,0 +1,107 @@
+use crate::{get_index, Game, PositionType};
+
+/// A cell where one extra obstruction traps the guard, with the number of
+/// steps of the loop it ends up in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Obstruction {
+    pub x: usize,
+    pub y: usize,
+    pub length: usize,
+}
+
+pub fn to_text(obstructions: &[Obstruction], lengths: bool) -> String {
+    let mut out = String::new();
+    for o in obstructions {
+        match lengths {
+            true => out.push_str(&format!("{},{} loop of {} steps\n", o.x, o.y, o.length)),
+            false => out.push_str(&format!("{},{}\n", o.x, o.y)),
+        }
+    }
+    out
+}
+
+pub fn to_json(obstructions: &[Obstruction], lengths: bool) -> String {
+    let obstructions: Vec<String> = obstructions
+        .iter()
+        .map(|o| match lengths {
+            true => format!("{{\"x\":{},\"y\":{},\"length\":{}}}", o.x, o.y, o.length),
+            false => format!("{{\"x\":{},\"y\":{}}}", o.x, o.y),
+        })
+        .collect();
+    format!("[{}]\n", obstructions.join(","))
+}
+
+/// The map before the patrol with the guard, its original path as `X` and
+/// every obstruction that traps it as `O`.
+pub fn render_overlay(game: &Game, patrol: &Game, obstructions: &[Obstruction]) -> String {
+    let mut cells: Vec<char> = patrol
+        .map
+        .iter()
+        .map(|pos| match (pos.pos_type, pos.visited) {
+            (PositionType::BLOCKED, _) => '#',
+            (PositionType::OPEN, true) => 'X',
+            (PositionType::OPEN, false) => '.',
+        })
+        .collect();
+    for o in obstructions {
+        let cell = &mut cells[get_index(o.x, o.y, game.max_y)];
+        debug_assert!(*cell != '#', "Obstruction on a wall at {},{}", o.x, o.y);
+        *cell = 'O';
+    }
+    let start = game.current_position;
+    cells[get_index(start.x, start.y, game.max_y)] = game.current_direction.glyph();
+    cells
+        .chunks(game.max_x + 1)
+        .map(|row| row.iter().collect::<String>() + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_game, find_loop_obstructions, parse_game, TurnRule};
+
+    #[test]
+    fn draws_obstructions_on_the_path() {
+        let game = build_game("example");
+        let mut patrol = game.clone();
+        patrol.run();
+        let obstructions = find_loop_obstructions(&game);
+        assert_eq!(
+            "....#.....\n\
+             ....XXXXX#\n\
+             ....X...X.\n\
+             ..#.X...X.\n\
+             ..XXXXX#X.\n\
+             ..X.X.X.X.\n\
+             .#XO^XXXX.\n\
+             .XXXXXOO#.\n\
+             #OXOXXXX..\n\
+             ......#O..\n",
+            render_overlay(&game, &patrol, &obstructions)
+        );
+        assert_eq!(
+            "[{\"x\":1,\"y\":8,\"length\":16}]\n",
+            to_json(&obstructions[..1], true)
+        );
+    }
+
+    #[test]
+    fn walls_and_start_stay_when_already_looping() {
+        let mut game = parse_game(".#..\n....\n.^..\n.#..");
+        game.rule = TurnRule::Around;
+        let mut patrol = game.clone();
+        patrol.run();
+        let obstructions = find_loop_obstructions(&game);
+        // Every open cell, the start included, where an obstruction only
+        // shortens the loop.
+        assert_eq!(14, obstructions.len());
+        assert_eq!(
+            "O#OO\n\
+             OOOO\n\
+             O^OO\n\
+             O#OO\n",
+            render_overlay(&game, &patrol, &obstructions)
+        );
+    }
+}
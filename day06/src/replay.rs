@@ -0,0 +1,196 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::Path,
+    thread,
+    time::Duration,
+};
+
+use crate::{get_index, Direction, Game, Outcome, PositionType};
+
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// The guard's state after one move of the patrol.
+struct Visit {
+    cell: usize,
+    direction: Direction,
+    steps: usize,
+    turned: bool,
+}
+
+/// The cells of the loop the guard ends up in, drawn the way the puzzle does:
+/// `|` and `-` for cells crossed in one direction, `+` where the guard turns
+/// or crosses its own path.
+fn loop_trail(history: &[Visit], outcome: Outcome) -> HashMap<usize, char> {
+    let mut trail = HashMap::new();
+    let Outcome::Looped { length, .. } = outcome else {
+        return trail;
+    };
+    let Some(last) = history.last() else {
+        return trail;
+    };
+    for visit in history.iter().filter(|v| v.steps + length >= last.steps) {
+        let c = match (visit.turned, visit.direction) {
+            (true, _) => '+',
+            (false, Direction::UP | Direction::DOWN) => '|',
+            (false, Direction::LEFT | Direction::RIGHT) => '-',
+        };
+        trail
+            .entry(visit.cell)
+            .and_modify(|old| {
+                if *old != c {
+                    *old = '+'
+                }
+            })
+            .or_insert(c);
+    }
+    trail
+}
+
+/// Draws the map with the guard and its heading, the cells it has visited as
+/// `X`, the placed obstruction as `O` and the loop trail, if any.
+fn render(
+    game: &Game,
+    block: Option<(usize, usize)>,
+    trail: &HashMap<usize, char>,
+    colour: bool,
+) -> String {
+    let mut out = String::new();
+    for y in 0..=game.max_y {
+        for x in 0..=game.max_x {
+            let position = game.map[get_index(x, y, game.max_y)];
+            let guard = x == game.current_position.x && y == game.current_position.y;
+            let trail = trail.get(&get_index(x, y, game.max_y));
+            match (guard, trail) {
+                (true, _) if colour => out.push_str(&format!(
+                    "{}{}{}",
+                    YELLOW,
+                    game.current_direction.glyph(),
+                    RESET
+                )),
+                (true, _) => out.push(game.current_direction.glyph()),
+                (false, Some(c)) if colour => out.push_str(&format!("{}{}{}", RED, c, RESET)),
+                (false, Some(c)) => out.push(*c),
+                (false, None) if block == Some((x, y)) => out.push('O'),
+                (false, None) => out.push(match (position.pos_type, position.visited) {
+                    (PositionType::BLOCKED, _) => '#',
+                    (PositionType::OPEN, true) => 'X',
+                    (PositionType::OPEN, false) => '.',
+                }),
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Replays the patrol, calling `on_frame` with the map before the first move,
+/// after every `every`-th move and once more at the end, with the loop the
+/// guard is stuck in highlighted if there is one.
+fn replay<F>(
+    game: &Game,
+    block: Option<(usize, usize)>,
+    every: usize,
+    colour: bool,
+    mut on_frame: F,
+) -> Outcome
+where
+    F: FnMut(String),
+{
+    let no_trail = HashMap::new();
+    on_frame(render(game, block, &no_trail, colour));
+    let mut patrol = game.clone();
+    let mut history = Vec::new();
+    let mut direction = game.current_direction;
+    let outcome = patrol.run_with(|state| {
+        history.push(Visit {
+            cell: get_index(
+                state.current_position.x,
+                state.current_position.y,
+                state.max_y,
+            ),
+            direction: state.current_direction,
+            steps: state.steps_taken,
+            turned: state.current_direction != direction,
+        });
+        direction = state.current_direction;
+        if history.len().is_multiple_of(every) {
+            on_frame(render(state, block, &no_trail, colour));
+        }
+    });
+    on_frame(render(
+        &patrol,
+        block,
+        &loop_trail(&history, outcome),
+        colour,
+    ));
+    outcome
+}
+
+/// Animates the patrol in the terminal, waiting `delay` between frames.
+pub fn animate(
+    game: &Game,
+    block: Option<(usize, usize)>,
+    every: usize,
+    delay: Duration,
+) -> Outcome {
+    let mut frame_number = 0;
+    replay(game, block, every, true, |frame| {
+        let mut stdout = io::stdout().lock();
+        write!(stdout, "\x1b[H\x1b[2JFrame {}\n{}", frame_number, frame).expect("Can write frame");
+        stdout.flush().expect("Can flush frame");
+        frame_number += 1;
+        thread::sleep(delay);
+    })
+}
+
+/// Writes the frames of the patrol as numbered text files into `dir`.
+pub fn write_frames(
+    game: &Game,
+    block: Option<(usize, usize)>,
+    every: usize,
+    dir: &str,
+) -> io::Result<Outcome> {
+    fs::create_dir_all(dir)?;
+    let mut frame_number = 0;
+    let mut written = Ok(());
+    let outcome = replay(game, block, every, false, |frame| {
+        if written.is_ok() {
+            let file_name = Path::new(dir).join(format!("frame_{:05}.txt", frame_number));
+            written = fs::write(file_name, frame);
+        }
+        frame_number += 1;
+    });
+    written.map(|_| outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build_game;
+
+    #[test]
+    fn highlights_loop_in_last_frame() {
+        let mut game = build_game("example");
+        game.add_block(3, 6);
+        let mut frames = Vec::new();
+        let outcome = replay(&game, Some((3, 6)), 10, false, |frame| frames.push(frame));
+        assert!(matches!(outcome, Outcome::Looped { .. }));
+        assert_eq!(
+            "....#.....\n\
+             ....>---+#\n\
+             ....|...|.\n\
+             ..#.|...|.\n\
+             ....|..#|.\n\
+             ....|...|.\n\
+             .#.O+---+.\n\
+             ........#.\n\
+             #.........\n\
+             ......#...\n",
+            frames.last().expect("Has frames")
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use crate::Direction;
+
+/// How the guard picks a new heading when it runs into an obstacle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TurnRule {
+    Right,
+    Left,
+    Around,
+    /// Right on the first turn, left on the second and so on.
+    Alternating,
+    /// Left or right, decided by hashing the seed with the guard's cell and
+    /// heading. The same situation always leads to the same turn, so a guard
+    /// that comes back to it is stuck in a loop just like with the other
+    /// rules.
+    Random(u64),
+}
+
+impl TurnRule {
+    /// Reads `right`, `left`, `around`, `alternate` or `random:SEED`.
+    pub fn from_spec(spec: &str) -> TurnRule {
+        match spec.split_once(':') {
+            Some(("random", seed)) => TurnRule::Random(seed.parse().expect("Seed is a number")),
+            _ => match spec {
+                "right" => TurnRule::Right,
+                "left" => TurnRule::Left,
+                "around" => TurnRule::Around,
+                "alternate" => TurnRule::Alternating,
+                _ => panic!("Unknown turning rule {}", spec),
+            },
+        }
+    }
+
+    /// Heading after the guard at `cell` (its map index) heading `direction`
+    /// turns for the time numbered `turns`, counting from zero.
+    pub fn turn(&self, cell: usize, direction: Direction, turns: usize) -> Direction {
+        match self {
+            TurnRule::Right => direction.turned_right(),
+            TurnRule::Left => direction.turned_left(),
+            TurnRule::Around => direction.turned_right().turned_right(),
+            TurnRule::Alternating if turns.is_multiple_of(2) => direction.turned_right(),
+            TurnRule::Alternating => direction.turned_left(),
+            TurnRule::Random(seed) => match mix(seed ^ (cell * 4 + direction.index()) as u64) & 1 {
+                0 => direction.turned_right(),
+                _ => direction.turned_left(),
+            },
+        }
+    }
+
+    /// The part of the number of turns taken so far that still matters for
+    /// later turns. It belongs to the guard's state when looking for loops.
+    pub fn phase(&self, turns: usize) -> usize {
+        match self {
+            TurnRule::Alternating => turns % 2,
+            _ => 0,
+        }
+    }
+}
+
+/// The splitmix64 finaliser, spreading every input bit over the output.
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
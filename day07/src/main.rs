@@ -1,113 +1,588 @@
-use std::{fs::File, io::Read, iter};
+mod number;
+mod operators;
+mod precedence;
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    env,
+    fs::File,
+    io::Read,
+    iter,
+};
 
 use itertools::Itertools;
+use num_bigint::BigUint;
 
-#[derive(Clone)]
-enum Operation {
-    ADD,
-    MULTIPLY,
-    CONCAT,
-}
+use number::Number;
+use operators::Operator;
+use precedence::Search;
 
 #[derive(Debug)]
-struct Equation {
-    result: usize,
-    components: Vec<usize>,
+struct Equation<N> {
+    result: N,
+    components: Vec<N>,
+}
+
+/// How the operators of an equation are worked out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Order {
+    /// Strictly from left to right, as in the puzzle.
+    LeftToRight,
+    /// Tighter binding operators first, such as multiplication before
+    /// addition, with concatenation binding tightest.
+    Precedence,
 }
 
-struct SolutionAttempt {
-    operations: Vec<Operation>,
+struct SolutionAttempt<N: 'static> {
+    operations: Vec<&'static dyn Operator<N>>,
 }
 
-impl From<Vec<char>> for SolutionAttempt {
-    fn from(values: Vec<char>) -> Self {
-        SolutionAttempt {
-            operations: values
+/// Works back from the result of an equation one component at a time.
+struct Solver<'a, N: 'static> {
+    components: &'a [N],
+    operators: &'a [&'static dyn Operator<N>],
+    /// Values the first n components can be combined into, indexed by n,
+    /// for operators that can not be undone.
+    reachable: Vec<Option<BTreeSet<N>>>,
+    /// Number of ways to combine the first n components into a value.
+    counted: HashMap<(N, usize), usize>,
+}
+
+impl<N: Number> Solver<'_, N> {
+    /// Values the first `count - 1` components can have been combined into
+    /// for `op` and the next component to give `target`. An operator is only
+    /// undone into the values that can have produced the target, such as the
+    /// quotient for multiplication when the target is divisible, so most
+    /// branches end after a step or two.
+    fn candidates(&mut self, op: &dyn Operator<N>, target: &N, count: usize) -> Vec<N> {
+        let components = self.components;
+        let last = &components[count - 1];
+        match op.undo(target, last) {
+            Some(candidates) => candidates,
+            None => self
+                .reachable(count - 1)
                 .iter()
-                .map(|c| match c {
-                    '+' => Operation::ADD,
-                    '*' => Operation::MULTIPLY,
-                    '|' => Operation::CONCAT,
-                    _ => unreachable!(),
-                })
+                .filter(|value| op.apply(value, last).as_ref() == Some(target))
+                .cloned()
                 .collect(),
         }
     }
+
+    /// Whether the first `count` components can be combined into `target`.
+    fn solveable(&mut self, target: &N, count: usize) -> bool {
+        if count == 1 {
+            return *target == self.components[0];
+        }
+        let operators = self.operators;
+        operators.iter().any(|op| {
+            self.candidates(*op, target, count)
+                .iter()
+                .any(|candidate| self.solveable(candidate, count - 1))
+        })
+    }
+
+    /// Number of operator sequences combining the first `count` components
+    /// into `target`.
+    fn count(&mut self, target: &N, count: usize) -> usize {
+        if count == 1 {
+            return usize::from(*target == self.components[0]);
+        }
+        if let Some(ways) = self.counted.get(&(target.clone(), count)) {
+            return *ways;
+        }
+        let operators = self.operators;
+        let mut ways = 0;
+        for op in operators {
+            for candidate in self.candidates(*op, target, count) {
+                ways += self.count(&candidate, count - 1);
+            }
+        }
+        self.counted.insert((target.clone(), count), ways);
+        ways
+    }
+
+    /// Adds up to `limit` operator sequences combining the first `count`
+    /// components into `target` to `found`, each completed by `suffix`.
+    fn solutions(
+        &mut self,
+        target: &N,
+        count: usize,
+        suffix: &mut Vec<&'static dyn Operator<N>>,
+        found: &mut Vec<Vec<&'static dyn Operator<N>>>,
+        limit: usize,
+    ) {
+        if count == 1 {
+            if *target == self.components[0] && found.len() < limit {
+                found.push(suffix.iter().rev().copied().collect());
+            }
+            return;
+        }
+        let operators = self.operators;
+        for op in operators {
+            for candidate in self.candidates(*op, target, count) {
+                if found.len() == limit {
+                    return;
+                }
+                suffix.push(*op);
+                self.solutions(&candidate, count - 1, suffix, found, limit);
+                suffix.pop();
+            }
+        }
+    }
+
+    /// Every value the first `count` components can be combined into.
+    fn reachable(&mut self, count: usize) -> &BTreeSet<N> {
+        if self.reachable[count].is_none() {
+            let values = match count {
+                1 => BTreeSet::from([self.components[0].clone()]),
+                _ => {
+                    let component = &self.components[count - 1];
+                    let operators = self.operators;
+                    self.reachable(count - 1)
+                        .iter()
+                        .flat_map(|value| {
+                            operators.iter().filter_map(|op| op.apply(value, component))
+                        })
+                        .collect()
+                }
+            };
+            self.reachable[count] = Some(values);
+        }
+        self.reachable[count].as_ref().expect("Was just filled in")
+    }
 }
 
-impl Equation {
-    fn is_solveable(&self, operators: Vec<char>) -> bool {
-        iter::repeat_n(operators, self.components.len() - 1)
-            .multi_cartesian_product()
-            .map(|att| att.into())
-            .any(|att| self.solves(att))
-    }
-
-    fn solves(&self, attempt: SolutionAttempt) -> bool {
-        let components = self.components.clone();
-        let operations = attempt.operations.clone();
-        let mut component_iter = components.iter();
-        let mut operations_iter = operations.iter();
-        let mut accumulator: usize = *component_iter.next().expect("has initial value");
-        loop {
-            match operations_iter.next() {
-                Some(op) => match op {
-                    Operation::ADD => {
-                        accumulator =
-                            accumulator + component_iter.next().expect("component should exist")
-                    }
-                    Operation::MULTIPLY => {
-                        accumulator =
-                            accumulator * component_iter.next().expect("component should exist")
-                    }
-                    Operation::CONCAT => {
-                        let next_comp = component_iter.next().expect("component should exist");
-                        let factor = 10_usize.pow(next_comp.to_string().len().try_into().unwrap());
-                        accumulator = accumulator * factor + next_comp;
-                    }
-                },
-                None => return accumulator == self.result,
+impl<N: Number> Equation<N> {
+    fn solver<'a>(&'a self, operators: &'a [&'static dyn Operator<N>]) -> Solver<'a, N> {
+        Solver {
+            components: &self.components,
+            operators,
+            reachable: vec![None; self.components.len() + 1],
+            counted: HashMap::new(),
+        }
+    }
+
+    fn search<'a>(&'a self, operators: &'a [&'static dyn Operator<N>]) -> Search<'a, N> {
+        Search {
+            result: &self.result,
+            components: &self.components,
+            operators,
+        }
+    }
+
+    fn is_solveable(&self, operators: &[&'static dyn Operator<N>], order: Order) -> bool {
+        match order {
+            Order::LeftToRight => self
+                .solver(operators)
+                .solveable(&self.result, self.components.len()),
+            Order::Precedence => !self.solutions(operators, order, 1).is_empty(),
+        }
+    }
+
+    /// Up to `limit` operator sequences that give the result.
+    fn solutions(
+        &self,
+        operators: &[&'static dyn Operator<N>],
+        order: Order,
+        limit: usize,
+    ) -> Vec<Vec<&'static dyn Operator<N>>> {
+        let mut found = Vec::new();
+        if limit == 0 {
+            return found;
+        }
+        match order {
+            Order::LeftToRight => self.solver(operators).solutions(
+                &self.result,
+                self.components.len(),
+                &mut Vec::new(),
+                &mut found,
+                limit,
+            ),
+            Order::Precedence => self.search(operators).run(&mut |operations| {
+                found.push(operations.to_vec());
+                found.len() < limit
+            }),
+        }
+        found
+    }
+
+    fn count_solutions(&self, operators: &[&'static dyn Operator<N>], order: Order) -> usize {
+        match order {
+            Order::LeftToRight => self
+                .solver(operators)
+                .count(&self.result, self.components.len()),
+            Order::Precedence => {
+                let mut ways = 0;
+                self.search(operators).run(&mut |_| {
+                    ways += 1;
+                    true
+                });
+                ways
             }
         }
     }
+
+    /// The equation with `operations` filled in, such as
+    /// `3267 = 81 + 40 * 27`.
+    fn render(&self, operations: &[&'static dyn Operator<N>]) -> String {
+        let mut out = format!("{} = {}", self.result, self.components[0]);
+        for (op, component) in operations.iter().zip(&self.components[1..]) {
+            out.push_str(&format!(" {} {}", op.rendered(), component));
+        }
+        out
+    }
+
+    /// Tries every combination of operators.
+    fn is_solveable_by_trying_all(
+        &self,
+        operators: &[&'static dyn Operator<N>],
+        order: Order,
+    ) -> bool {
+        iter::repeat_n(operators.to_vec(), self.components.len() - 1)
+            .multi_cartesian_product()
+            .map(|operations| SolutionAttempt { operations })
+            .any(|att| self.solves(att, order))
+    }
+
+    /// Whether the operations give the result, without any step on the way
+    /// failing or overflowing.
+    fn solves(&self, attempt: SolutionAttempt<N>, order: Order) -> bool {
+        if order == Order::Precedence {
+            return precedence::evaluate(&self.components, &attempt.operations).as_ref()
+                == Some(&self.result);
+        }
+        let mut component_iter = self.components.iter();
+        let mut accumulator: Option<N> = component_iter.next().cloned();
+        for op in attempt.operations {
+            let component = component_iter.next().expect("component should exist");
+            accumulator = accumulator.and_then(|value| op.apply(&value, component));
+        }
+        accumulator.as_ref() == Some(&self.result)
+    }
 }
 
-fn main() {
-    let file_name = "input";
-    let mut input_file = File::open(file_name).expect("Can open file");
-    let mut content = String::new();
-    input_file
-        .read_to_string(&mut content)
-        .expect("Can read file");
+fn parse_number<N: Number>(value: &str) -> N {
+    value
+        .parse()
+        .unwrap_or_else(|_| panic!("{:?} is not an integer, or too large without --big", value))
+}
 
-    let equations: Vec<Equation> = content
-        .split("\n")
+fn parse_equations<N: Number>(content: &str) -> Vec<Equation<N>> {
+    content
+        .lines()
         .map(|line| {
             let mut split_line = line.split(": ");
-            let result: usize = split_line
-                .next()
-                .expect("Has result")
-                .parse()
-                .expect("result is an integer");
-            let components: Vec<usize> = split_line
+            let result: N = parse_number(split_line.next().expect("Has result"));
+            let components: Vec<N> = split_line
                 .next()
                 .expect("Has components")
                 .split(" ")
-                .map(|val| val.parse().expect("component is an integer"))
+                .map(parse_number)
                 .collect();
             Equation { result, components }
         })
-        .collect();
-    let part_one_solution: usize = equations
-        .iter()
-        .filter(|eq| eq.is_solveable(['+', '*'].to_vec()))
-        .map(|eq| eq.result)
-        .sum();
-    println!("Part one: {}", part_one_solution);
-    let part_two_solution: usize = equations
+        .collect()
+}
+
+/// What to print for every set of operators.
+enum Show {
+    /// Only the sum of the results of the solveable equations.
+    Sum,
+    /// One way to solve every solveable equation.
+    First,
+    All,
+    /// How many ways there are to solve every solveable equation.
+    Count,
+}
+
+struct Options {
+    file_name: String,
+    show: Show,
+    /// Try every combination of operators instead of working backwards.
+    brute_force: bool,
+    /// Solve with only the operators in this list instead of solving both
+    /// parts.
+    operators: Option<String>,
+    /// Work with numbers of any size instead of `usize`.
+    big: bool,
+    /// Every order to solve the equations in, one after the other.
+    orders: Vec<Order>,
+}
+
+/// Reads `[--brute-force] [--operators LIST] [--show first|all|count] [--big]
+/// [--order left|precedence|both] [--input FILE]`. Without arguments both
+/// parts are solved for "input" by working back from the results, dropping
+/// every way to solve an equation that overflows a `usize` on the way. `--big`
+/// works with numbers of any size instead. LIST is a comma separated list of
+/// operator symbols out of `+ * | - / ^ xor min max`, such as `+,*,-`.
+/// `--show` lists the solveable equations below each sum, with one or all of
+/// the ways to solve them or only the number of ways. `--order` solves with
+/// multiplication before addition and concatenation before both instead of
+/// from left to right, or reports the sums for both orders.
+fn parse_options() -> Options {
+    let mut options = Options {
+        file_name: "input".to_string(),
+        show: Show::Sum,
+        brute_force: false,
+        operators: None,
+        big: false,
+        orders: vec![Order::LeftToRight],
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => options.file_name = args.next().expect("--input needs a file name"),
+            "--brute-force" => options.brute_force = true,
+            "--show" => {
+                options.show = match args.next().expect("--show needs a mode").as_str() {
+                    "first" => Show::First,
+                    "all" => Show::All,
+                    "count" => Show::Count,
+                    mode => panic!("Unknown mode {}", mode),
+                }
+            }
+            "--operators" => {
+                options.operators = Some(args.next().expect("--operators needs a list of symbols"))
+            }
+            "--big" => options.big = true,
+            "--order" => {
+                options.orders = match args.next().expect("--order needs an order").as_str() {
+                    "left" => vec![Order::LeftToRight],
+                    "precedence" => vec![Order::Precedence],
+                    "both" => vec![Order::LeftToRight, Order::Precedence],
+                    order => panic!("Unknown order {}", order),
+                }
+            }
+            _ => panic!("Unknown argument {}", arg),
+        }
+    }
+    options
+}
+
+fn sum_solveable<N: Number>(
+    equations: &[Equation<N>],
+    operators: &[&'static dyn Operator<N>],
+    order: Order,
+    brute_force: bool,
+) -> N {
+    equations
         .iter()
-        .filter(|eq| eq.is_solveable(['+', '*', '|'].to_vec()))
-        .map(|eq| eq.result)
-        .sum();
-    println!("Part two: {}", part_two_solution);
+        .filter(|eq| match brute_force {
+            true => eq.is_solveable_by_trying_all(operators, order),
+            false => eq.is_solveable(operators, order),
+        })
+        .fold(N::zero(), |sum, eq| {
+            sum.checked_add(&eq.result)
+                .expect("Sum is too large without --big")
+        })
+}
+
+fn solve<N: Number>(content: &str, options: &Options) {
+    let equations: Vec<Equation<N>> = parse_equations(content);
+    let runs = match &options.operators {
+        Some(spec) => {
+            let operators = operators::from_spec(spec);
+            let symbols: Vec<&str> = operators.iter().map(|op| op.symbol()).collect();
+            vec![(format!("Operators {}", symbols.join(" ")), operators)]
+        }
+        None => vec![
+            ("Part one".to_string(), operators::from_spec("+,*")),
+            ("Part two".to_string(), operators::from_spec("+,*,|")),
+        ],
+    };
+    for (label, operators) in runs {
+        for order in options.orders.iter().copied() {
+            let label = match order {
+                Order::LeftToRight => label.clone(),
+                Order::Precedence => format!("{} with precedence", label),
+            };
+            report(&equations, &label, &operators, order, options);
+        }
+    }
+}
+
+/// Prints the sum for one set of operators, and below it whatever `--show`
+/// asks for.
+fn report<N: Number>(
+    equations: &[Equation<N>],
+    label: &str,
+    operators: &[&'static dyn Operator<N>],
+    order: Order,
+    options: &Options,
+) {
+    let solution = sum_solveable(equations, operators, order, options.brute_force);
+    println!("{}: {}", label, solution);
+    for eq in equations {
+        match options.show {
+            Show::Sum => {}
+            Show::First | Show::All => {
+                let limit = match options.show {
+                    Show::First => 1,
+                    _ => usize::MAX,
+                };
+                for solution in eq.solutions(operators, order, limit) {
+                    println!("  {}", eq.render(&solution));
+                }
+            }
+            Show::Count => match eq.count_solutions(operators, order) {
+                0 => {}
+                1 => println!("  {}: 1 solution", eq.result),
+                ways => println!("  {}: {} solutions", eq.result, ways),
+            },
+        }
+    }
+}
+
+fn main() {
+    let options = parse_options();
+    let mut input_file = File::open(&options.file_name).expect("Can open file");
+    let mut content = String::new();
+    input_file
+        .read_to_string(&mut content)
+        .expect("Can read file");
+
+    match options.big {
+        true => solve::<BigUint>(&content, &options),
+        false => solve::<usize>(&content, &options),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Vec<Equation<usize>> {
+        parse_equations(&std::fs::read_to_string("example").expect("Can read file"))
+    }
+
+    #[test]
+    fn example_sums() {
+        let part_one = operators::from_spec("+,*");
+        let part_two = operators::from_spec("+,*,|");
+        for brute_force in [false, true] {
+            assert_eq!(
+                3749,
+                sum_solveable(&example(), &part_one, Order::LeftToRight, brute_force)
+            );
+            assert_eq!(
+                11387,
+                sum_solveable(&example(), &part_two, Order::LeftToRight, brute_force)
+            );
+            assert_eq!(
+                3457,
+                sum_solveable(&example(), &part_one, Order::Precedence, brute_force)
+            );
+            assert_eq!(
+                3805,
+                sum_solveable(&example(), &part_two, Order::Precedence, brute_force)
+            );
+        }
+    }
+
+    #[test]
+    fn renders_every_solution() {
+        let equations = example();
+        let operators = operators::from_spec("+,*,|");
+        let rendered: Vec<String> = equations
+            .iter()
+            .flat_map(|eq| {
+                eq.solutions(&operators, Order::LeftToRight, usize::MAX)
+                    .into_iter()
+                    .map(|solution| eq.render(&solution))
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                "190 = 10 * 19",
+                "3267 = 81 * 40 + 27",
+                "3267 = 81 + 40 * 27",
+                "156 = 15 || 6",
+                "7290 = 6 * 8 || 6 * 15",
+                "192 = 17 || 8 + 14",
+                "292 = 11 + 6 * 16 + 20",
+            ],
+            rendered
+        );
+        let counts: Vec<usize> = equations
+            .iter()
+            .map(|eq| eq.count_solutions(&operators, Order::LeftToRight))
+            .collect();
+        assert_eq!(vec![1, 2, 0, 1, 1, 0, 1, 0, 1], counts);
+    }
+
+    #[test]
+    fn overflowing_branches_are_dropped() {
+        // 2^63 * 2 wraps around to 0 and 2^63 + 2^63 to 0 as well.
+        let equations: Vec<Equation<usize>> =
+            parse_equations("0: 9223372036854775808 2\n0: 9223372036854775808 9223372036854775808");
+        let operators = operators::from_spec("+,*,|");
+        for eq in &equations {
+            assert!(!eq.is_solveable(&operators, Order::LeftToRight));
+            assert!(!eq.is_solveable_by_trying_all(&operators, Order::LeftToRight));
+        }
+    }
+
+    #[test]
+    fn big_numbers_are_exact() {
+        let content = "18446744073709551616: 4294967296 4294967296\n\
+                       123456789012345678901234567890: 1234567890 1234567890 1234567890\n\
+                       18446744073709551617: 4294967296 4294967296";
+        let equations: Vec<Equation<BigUint>> = parse_equations(content);
+        let operators = operators::from_spec("+,*,|");
+        let solveable: Vec<bool> = equations
+            .iter()
+            .map(|eq| eq.is_solveable(&operators, Order::LeftToRight))
+            .collect();
+        assert_eq!(vec![true, true, false], solveable);
+        assert_eq!(
+            "123456789012345678901234567890 = 1234567890 || 1234567890 || 1234567890",
+            equations[1].render(&equations[1].solutions(&operators, Order::LeftToRight, 1)[0])
+        );
+    }
+
+    #[test]
+    fn backwards_agrees_with_trying_all() {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |below: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % below) as usize
+        };
+        for _ in 0..5000 {
+            let components: Vec<usize> = (0..1 + next(5)).map(|_| next(12)).collect();
+            // Small results, or results near a reachable value.
+            let result = match next(2) {
+                0 => next(60),
+                _ => components.iter().product::<usize>() + next(3) * next(40),
+            };
+            let equation = Equation { result, components };
+            for spec in ["+,*", "+,*,|", "|", "-,/,^", "xor,min,max,+", "*,-"] {
+                let operators = operators::from_spec(spec);
+                for order in [Order::LeftToRight, Order::Precedence] {
+                    let all = iter::repeat_n(operators.clone(), equation.components.len() - 1)
+                        .multi_cartesian_product()
+                        .filter(|operations| {
+                            equation.solves(
+                                SolutionAttempt {
+                                    operations: operations.clone(),
+                                },
+                                order,
+                            )
+                        })
+                        .count();
+                    assert_eq!(
+                        equation.is_solveable_by_trying_all(&operators, order),
+                        equation.is_solveable(&operators, order),
+                        "{:?} {} {:?}",
+                        equation,
+                        spec,
+                        order
+                    );
+                    assert_eq!(all, equation.count_solutions(&operators, order));
+                    assert_eq!(all, equation.solutions(&operators, order, usize::MAX).len());
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,137 @@
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    str::FromStr,
+};
+
+use num_bigint::BigUint;
+
+/// The numbers equations are solved in: `usize`, where an operation that
+/// would overflow fails so that its branch is dropped, or `BigUint` when
+/// large numbers have to be handled exactly.
+pub trait Number: Clone + Ord + Hash + Debug + Display + FromStr + 'static {
+    fn zero() -> Self;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+    /// Quotient and remainder, `None` when dividing by zero.
+    fn checked_div_rem(&self, other: &Self) -> Option<(Self, Self)>;
+    fn checked_pow(&self, exponent: &Self) -> Option<Self>;
+    fn xor(&self, other: &Self) -> Self;
+    /// Ten to the number of digits of `self`, which a number is multiplied
+    /// by when `self` is concatenated to it.
+    fn concat_factor(&self) -> Option<Self>;
+}
+
+impl Number for usize {
+    fn zero() -> Self {
+        0
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        usize::checked_add(*self, *other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        usize::checked_sub(*self, *other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        usize::checked_mul(*self, *other)
+    }
+
+    fn checked_div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        Some((self.checked_div(*other)?, self % other))
+    }
+
+    fn checked_pow(&self, exponent: &Self) -> Option<Self> {
+        usize::checked_pow(*self, (*exponent).try_into().ok()?)
+    }
+
+    fn xor(&self, other: &Self) -> Self {
+        self ^ other
+    }
+
+    fn concat_factor(&self) -> Option<Self> {
+        10_usize.checked_pow(self.to_string().len().try_into().ok()?)
+    }
+}
+
+/// Powers with a larger exponent are not worked out. Anything but zero or
+/// one raised to them has tens of thousands of digits.
+const MAX_BIG_EXPONENT: u32 = 1 << 16;
+
+impl Number for BigUint {
+    fn zero() -> Self {
+        BigUint::ZERO
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        match self >= other {
+            true => Some(self - other),
+            false => None,
+        }
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+
+    fn checked_div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        match *other == BigUint::ZERO {
+            true => None,
+            false => Some((self / other, self % other)),
+        }
+    }
+
+    fn checked_pow(&self, exponent: &Self) -> Option<Self> {
+        if *self <= BigUint::from(1_u32) {
+            return Some(match *exponent == BigUint::ZERO {
+                true => BigUint::from(1_u32),
+                false => self.clone(),
+            });
+        }
+        let exponent = u32::try_from(exponent).ok()?;
+        match exponent <= MAX_BIG_EXPONENT {
+            true => Some(self.pow(exponent)),
+            false => None,
+        }
+    }
+
+    fn xor(&self, other: &Self) -> Self {
+        self ^ other
+    }
+
+    fn concat_factor(&self) -> Option<Self> {
+        Some(BigUint::from(10_u32).pow(self.to_string().len().try_into().ok()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usize_fails_where_big_numbers_grow() {
+        let large: usize = 1 << 63;
+        assert_eq!(None, Number::checked_mul(&large, &2));
+        assert_eq!(None, Number::checked_add(&usize::MAX, &1));
+        assert_eq!(None, usize::MAX.concat_factor());
+        assert_eq!(Some(100), 42_usize.concat_factor());
+        let big = BigUint::from(large);
+        assert_eq!(
+            "18446744073709551616",
+            Number::checked_mul(&big, &BigUint::from(2_u32))
+                .expect("Big numbers do not overflow")
+                .to_string()
+        );
+        assert_eq!(
+            Some(BigUint::from(1_u32)),
+            BigUint::from(1_u32).checked_pow(&BigUint::from(u64::MAX))
+        );
+    }
+}
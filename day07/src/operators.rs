@@ -0,0 +1,289 @@
+use crate::number::Number;
+
+/// An operator placed between the value so far and the next component.
+/// Equations are evaluated from left to right, so `apply` always gets the
+/// whole left hand side as `accumulator`, unless they are evaluated with
+/// precedence.
+pub trait Operator<N>: Sync {
+    fn symbol(&self) -> &'static str;
+
+    /// How tightly the operator binds when evaluating with precedence, higher
+    /// binding tighter. Operators of the same level group left to right,
+    /// unless they are right associative.
+    fn precedence(&self) -> u8;
+
+    /// Whether `a op b op c` means `a op (b op c)` when evaluating with
+    /// precedence, as for powers.
+    fn right_associative(&self) -> bool {
+        false
+    }
+
+    /// How the operator is written in a solved equation.
+    fn rendered(&self) -> &'static str {
+        self.symbol()
+    }
+
+    /// The combined value, or `None` where the operator is not defined or
+    /// the value does not fit.
+    fn apply(&self, accumulator: &N, component: &N) -> Option<N>;
+
+    /// Every accumulator that gives `result` when combined with `component`.
+    /// `None` means the operator can not be undone here, and the solver
+    /// tries every value the earlier components can reach instead.
+    fn undo(&self, _result: &N, _component: &N) -> Option<Vec<N>> {
+        None
+    }
+}
+
+struct Add;
+struct Multiply;
+struct Concat;
+struct Subtract;
+struct Divide;
+struct Power;
+struct Xor;
+struct Min;
+struct Max;
+
+impl<N: Number> Operator<N> for Add {
+    fn symbol(&self) -> &'static str {
+        "+"
+    }
+
+    fn precedence(&self) -> u8 {
+        2
+    }
+
+    fn apply(&self, accumulator: &N, component: &N) -> Option<N> {
+        accumulator.checked_add(component)
+    }
+
+    fn undo(&self, result: &N, component: &N) -> Option<Vec<N>> {
+        Some(result.checked_sub(component).into_iter().collect())
+    }
+}
+
+impl<N: Number> Operator<N> for Multiply {
+    fn symbol(&self) -> &'static str {
+        "*"
+    }
+
+    fn precedence(&self) -> u8 {
+        3
+    }
+
+    fn apply(&self, accumulator: &N, component: &N) -> Option<N> {
+        accumulator.checked_mul(component)
+    }
+
+    fn undo(&self, result: &N, component: &N) -> Option<Vec<N>> {
+        // Anything times zero is zero, so dividing by zero gives `None`.
+        let (quotient, remainder) = result.checked_div_rem(component)?;
+        match remainder == N::zero() {
+            true => Some(vec![quotient]),
+            false => Some(Vec::new()),
+        }
+    }
+}
+
+impl<N: Number> Operator<N> for Concat {
+    fn symbol(&self) -> &'static str {
+        "|"
+    }
+
+    fn precedence(&self) -> u8 {
+        5
+    }
+
+    fn rendered(&self) -> &'static str {
+        "||"
+    }
+
+    fn apply(&self, accumulator: &N, component: &N) -> Option<N> {
+        match component.concat_factor() {
+            Some(factor) => accumulator.checked_mul(&factor)?.checked_add(component),
+            // Only zero can be shifted that far.
+            None if *accumulator == N::zero() => Some(component.clone()),
+            None => None,
+        }
+    }
+
+    fn undo(&self, result: &N, component: &N) -> Option<Vec<N>> {
+        let Some(factor) = component.concat_factor() else {
+            return Some(match result == component {
+                true => vec![N::zero()],
+                false => Vec::new(),
+            });
+        };
+        let (quotient, remainder) = result.checked_div_rem(&factor)?;
+        match remainder == *component {
+            true => Some(vec![quotient]),
+            false => Some(Vec::new()),
+        }
+    }
+}
+
+impl<N: Number> Operator<N> for Subtract {
+    fn symbol(&self) -> &'static str {
+        "-"
+    }
+
+    fn precedence(&self) -> u8 {
+        2
+    }
+
+    /// Only defined when the result is not negative.
+    fn apply(&self, accumulator: &N, component: &N) -> Option<N> {
+        accumulator.checked_sub(component)
+    }
+
+    fn undo(&self, result: &N, component: &N) -> Option<Vec<N>> {
+        Some(result.checked_add(component).into_iter().collect())
+    }
+}
+
+impl<N: Number> Operator<N> for Divide {
+    fn symbol(&self) -> &'static str {
+        "/"
+    }
+
+    fn precedence(&self) -> u8 {
+        3
+    }
+
+    /// Integer division, rounding down.
+    fn apply(&self, accumulator: &N, component: &N) -> Option<N> {
+        accumulator
+            .checked_div_rem(component)
+            .map(|(quotient, _)| quotient)
+    }
+}
+
+impl<N: Number> Operator<N> for Power {
+    fn symbol(&self) -> &'static str {
+        "^"
+    }
+
+    fn precedence(&self) -> u8 {
+        4
+    }
+
+    fn right_associative(&self) -> bool {
+        true
+    }
+
+    fn apply(&self, accumulator: &N, component: &N) -> Option<N> {
+        accumulator.checked_pow(component)
+    }
+}
+
+impl<N: Number> Operator<N> for Xor {
+    fn symbol(&self) -> &'static str {
+        "xor"
+    }
+
+    fn precedence(&self) -> u8 {
+        1
+    }
+
+    fn apply(&self, accumulator: &N, component: &N) -> Option<N> {
+        Some(accumulator.xor(component))
+    }
+
+    fn undo(&self, result: &N, component: &N) -> Option<Vec<N>> {
+        Some(vec![result.xor(component)])
+    }
+}
+
+impl<N: Number> Operator<N> for Min {
+    fn symbol(&self) -> &'static str {
+        "min"
+    }
+
+    fn precedence(&self) -> u8 {
+        1
+    }
+
+    fn apply(&self, accumulator: &N, component: &N) -> Option<N> {
+        Some(accumulator.clone().min(component.clone()))
+    }
+}
+
+impl<N: Number> Operator<N> for Max {
+    fn symbol(&self) -> &'static str {
+        "max"
+    }
+
+    fn precedence(&self) -> u8 {
+        1
+    }
+
+    fn apply(&self, accumulator: &N, component: &N) -> Option<N> {
+        Some(accumulator.clone().max(component.clone()))
+    }
+}
+
+/// Every operator that can be picked, each by its symbol.
+fn all<N: Number>() -> [&'static dyn Operator<N>; 9] {
+    [
+        &Add, &Multiply, &Concat, &Subtract, &Divide, &Power, &Xor, &Min, &Max,
+    ]
+}
+
+pub fn lookup<N: Number>(symbol: &str) -> Option<&'static dyn Operator<N>> {
+    all().into_iter().find(|op| op.symbol() == symbol)
+}
+
+/// Looks up a comma separated list of symbols such as `+,*,|`.
+pub fn from_spec<N: Number>(spec: &str) -> Vec<&'static dyn Operator<N>> {
+    spec.split(',')
+        .map(|symbol| {
+            lookup(symbol.trim()).unwrap_or_else(|| {
+                let known: Vec<&str> = all::<N>().iter().map(|op| op.symbol()).collect();
+                panic!(
+                    "Unknown operator {:?}, known are {}",
+                    symbol,
+                    known.join(" ")
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    #[test]
+    fn undo_inverts_apply() {
+        let symbols: BTreeSet<&str> = all::<usize>().iter().map(|op| op.symbol()).collect();
+        assert_eq!(all::<usize>().len(), symbols.len(), "Symbols are unique");
+        for op in all::<usize>() {
+            let symbol = op.symbol();
+            let big = lookup::<BigUint>(symbol).expect("Symbol is known");
+            assert_eq!(symbol, big.symbol());
+            for accumulator in 0..40_usize {
+                for component in 0..15_usize {
+                    let Some(result) = op.apply(&accumulator, &component) else {
+                        continue;
+                    };
+                    // Where the result fits, big numbers agree with it.
+                    assert_eq!(
+                        Some(BigUint::from(result)),
+                        big.apply(&accumulator.into(), &component.into())
+                    );
+                    if let Some(candidates) = op.undo(&result, &component) {
+                        assert!(candidates.contains(&accumulator), "{}", symbol);
+                        assert!(candidates
+                            .iter()
+                            .all(|c| op.apply(c, &component) == Some(result)));
+                    }
+                }
+            }
+        }
+    }
+}
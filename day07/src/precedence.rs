@@ -0,0 +1,126 @@
+use crate::{number::Number, operators::Operator};
+
+/// Left hand sides still waiting for an operator that binds more loosely
+/// than the ones after it, from the outermost in.
+type Pending<N> = Vec<(N, &'static dyn Operator<N>)>;
+
+/// Applies every pending operator that has to be worked out before `next`
+/// to `value`, innermost first: those binding tighter, and those binding as
+/// tightly unless `next` is right associative. Without `next` every pending
+/// operator is applied. `None` when any of them fails.
+fn reduce<N: Number>(
+    pending: &mut Pending<N>,
+    mut value: N,
+    next: Option<&dyn Operator<N>>,
+) -> Option<N> {
+    while let Some((left, op)) = pending.last() {
+        if let Some(next) = next {
+            let first = match next.right_associative() {
+                true => op.precedence() > next.precedence(),
+                false => op.precedence() >= next.precedence(),
+            };
+            if !first {
+                break;
+            }
+        }
+        value = op.apply(left, &value)?;
+        pending.pop();
+    }
+    Some(value)
+}
+
+/// The value of `components` joined by `operations`, with tighter binding
+/// operators worked out first.
+pub fn evaluate<N: Number>(components: &[N], operations: &[&'static dyn Operator<N>]) -> Option<N> {
+    let mut pending = Vec::new();
+    let mut value = components[0].clone();
+    for (op, component) in operations.iter().zip(&components[1..]) {
+        value = reduce(&mut pending, value, Some(*op))?;
+        pending.push((value, *op));
+        value = component.clone();
+    }
+    reduce(&mut pending, value, None)
+}
+
+/// Tries the operators from left to right, sharing the work for a common
+/// start between the sequences. Working backwards from the result does not
+/// carry over, as the last operator is not necessarily applied last.
+pub struct Search<'a, N: 'static> {
+    pub result: &'a N,
+    pub components: &'a [N],
+    pub operators: &'a [&'static dyn Operator<N>],
+}
+
+impl<N: Number> Search<'_, N> {
+    /// Calls `found` with every operator sequence giving the result, until
+    /// it returns false.
+    pub fn run(&self, found: &mut impl FnMut(&[&'static dyn Operator<N>]) -> bool) {
+        self.descend(
+            Vec::new(),
+            self.components[0].clone(),
+            &mut Vec::new(),
+            found,
+        );
+    }
+
+    /// Returns false once the search is to stop.
+    fn descend(
+        &self,
+        pending: Pending<N>,
+        value: N,
+        operations: &mut Vec<&'static dyn Operator<N>>,
+        found: &mut impl FnMut(&[&'static dyn Operator<N>]) -> bool,
+    ) -> bool {
+        let next = operations.len() + 1;
+        if next == self.components.len() {
+            return match reduce(&mut pending.clone(), value, None) {
+                Some(value) if value == *self.result => found(operations),
+                _ => true,
+            };
+        }
+        for op in self.operators {
+            let mut pending = pending.clone();
+            let Some(left) = reduce(&mut pending, value.clone(), Some(*op)) else {
+                continue;
+            };
+            pending.push((left, *op));
+            operations.push(*op);
+            let go_on = self.descend(pending, self.components[next].clone(), operations, found);
+            operations.pop();
+            if !go_on {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operators;
+
+    #[test]
+    fn tighter_operators_go_first() {
+        let ops = operators::from_spec::<usize>("+,*,|,-,^");
+        let (add, multiply, concat, subtract, power) = (ops[0], ops[1], ops[2], ops[3], ops[4]);
+        assert_eq!(Some(1161), evaluate(&[81, 40, 27], &[add, multiply]));
+        assert_eq!(Some(93), evaluate(&[1, 2, 3, 4], &[add, concat, multiply]));
+        assert_eq!(Some(1), evaluate(&[5, 3, 1], &[subtract, subtract]));
+        assert_eq!(Some(38), evaluate(&[3, 2, 4, 2], &[power, multiply, add]));
+        assert_eq!(None, evaluate(&[1, 2, 3], &[subtract, multiply]));
+    }
+
+    #[test]
+    fn powers_group_right_to_left() {
+        let ops = operators::from_spec::<usize>("^,*,-");
+        let (power, multiply, subtract) = (ops[0], ops[1], ops[2]);
+        assert_eq!(Some(512), evaluate(&[2, 3, 2], &[power, power]));
+        assert_eq!(Some(2), evaluate(&[2, 1, 3, 2], &[power, power, power]));
+        assert_eq!(Some(16), evaluate(&[2, 2, 2, 2], &[power, multiply, power]));
+        assert_eq!(
+            Some(3),
+            evaluate(&[2, 3, 2, 3], &[power, subtract, subtract])
+        );
+    }
+}